@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+use circuit_playground_express as hal;
+extern crate panic_halt;
+
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use hal::clock::GenericClockController;
+use hal::delay::Delay;
+use hal::pac::{CorePeripherals, Peripherals};
+use hal::prelude::*;
+use hal::sercom::{I2CMaster1, PadPin};
+use hal::time::KiloHertz;
+
+use lis3dh::{
+    Interrupt1, InterruptConfig, InterruptMode, Lis3dh, OrientationDebouncer, Range, SlaveAddr,
+    Threshold,
+};
+
+#[entry]
+fn main() -> ! {
+    let mut peripherals = Peripherals::take().unwrap();
+    let core = CorePeripherals::take().unwrap();
+    let mut clocks = GenericClockController::with_internal_32kosc(
+        peripherals.GCLK,
+        &mut peripherals.PM,
+        &mut peripherals.SYSCTRL,
+        &mut peripherals.NVMCTRL,
+    );
+    let mut pins = hal::Pins::new(peripherals.PORT);
+    let gclk0 = clocks.gclk0();
+
+    let i2c = I2CMaster1::new(
+        &clocks.sercom1_core(&gclk0).unwrap(),
+        KiloHertz(400),
+        peripherals.SERCOM1,
+        &mut peripherals.PM,
+        pins.accel_sda.into_pad(&mut pins.port),
+        pins.accel_scl.into_pad(&mut pins.port),
+    );
+
+    let mut lis3dh = Lis3dh::new_i2c(i2c, SlaveAddr::Alternate).unwrap();
+    lis3dh.set_range(Range::G2).unwrap();
+
+    // let the device recognise the position instead of using a `Tracker`
+    let threshold = Threshold::g(Range::G2, 0.5).unwrap();
+    lis3dh
+        .configure_irq_threshold(Interrupt1, threshold)
+        .unwrap();
+    lis3dh
+        .configure_irq_src(
+            Interrupt1,
+            InterruptMode::Position,
+            InterruptConfig::high_and_low(),
+        )
+        .unwrap();
+
+    let mut delay = Delay::new(core.SYST, &mut clocks);
+    let mut debouncer = OrientationDebouncer::new(3);
+
+    loop {
+        if let Some(orientation) = debouncer.poll(&mut lis3dh, Interrupt1).unwrap() {
+            hprintln!("{:?}", orientation).ok();
+        }
+        delay.delay_ms(100u16)
+    }
+}
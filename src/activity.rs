@@ -0,0 +1,254 @@
+use crate::events::Event;
+use crate::register::DataRate;
+
+/// Sleep-to-wake settings, see [`Lis3dh::configure_activity`].
+///
+/// While no acceleration above `threshold_mg` is measured for
+/// `return_to_sleep_s` seconds, the device drops to low-power mode at a
+/// reduced data rate. It returns to the configured mode and data rate as
+/// soon as the threshold is exceeded again.
+///
+/// [`Lis3dh::configure_activity`]: crate::Lis3dh::configure_activity
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[doc(alias = "ACT_THS")]
+#[doc(alias = "ACT_DUR")]
+pub struct ActivityConfig {
+    /// Wake-up threshold in mili-g, default `100.0`
+    pub threshold_mg: f32,
+
+    /// Inactivity time before returning to sleep in seconds, default `1.0`
+    pub return_to_sleep_s: f32,
+
+    /// Polarity of the interrupt pins, `Some(true)` for active low. Note
+    /// that this setting applies to both `INT1` and `INT2`. Default `None`
+    /// keeps the current polarity.
+    pub active_low: Option<bool>,
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            threshold_mg: 100.0,
+            return_to_sleep_s: 1.0,
+            active_low: None,
+        }
+    }
+}
+
+/// Whether the device is awake or in its low-power sleep state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ActivityState {
+    Active,
+    Inactive,
+}
+
+impl ActivityState {
+    const fn event(self) -> Event {
+        match self {
+            ActivityState::Active => Event::Activity,
+            ActivityState::Inactive => Event::Inactivity,
+        }
+    }
+}
+
+/// Tracks sleep and wake transitions of a device configured by
+/// [`Lis3dh::configure_activity`].
+///
+/// The activity state is not visible in any register, it is inferred either
+/// from the `INT2` pin, which is asserted while the device sleeps, or from
+/// the time between data-ready samples, which grows when the device drops to
+/// its sleep data rate of 10Hz.
+///
+///     // let mut lis3dh = ...
+///     lis3dh.configure_activity(ActivityConfig::default())?;
+///
+///     let mut tracker = ActivityTracker::new(false, DataRate::Hz_400);
+///     loop {
+///         match tracker.update_from_pin(int2.is_high()?) {
+///             Some(Event::Inactivity) => { /* going to sleep */ }
+///             Some(Event::Activity) => { /* woke up */ }
+///             _ => {}
+///         }
+///     }
+///
+/// [`Lis3dh::configure_activity`]: crate::Lis3dh::configure_activity
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActivityTracker {
+    state: Option<ActivityState>,
+    active_low: bool,
+    sleep_interval_us: u32,
+}
+
+impl ActivityTracker {
+    /// Create a tracker for a device with the given interrupt polarity and
+    /// (awake) output data rate.
+    pub fn new(active_low: bool, datarate: DataRate) -> Self {
+        // Anything slower than half the configured data rate is assumed to be
+        // the sleep data rate. This only works for data rates above 10Hz.
+        let sleep_interval_us = match datarate {
+            DataRate::Hz_10 | DataRate::Hz_1 | DataRate::PowerDown => u32::MAX,
+            datarate => 2 * (1_000_000.0 / datarate.sample_rate()) as u32,
+        };
+
+        Self {
+            state: None,
+            active_low,
+            sleep_interval_us,
+        }
+    }
+
+    /// The current state, `None` until the first update.
+    pub const fn state(&self) -> Option<ActivityState> {
+        self.state
+    }
+
+    /// Update with the level of the `INT2` pin (`true` when high). Returns
+    /// [`Event::Activity`] or [`Event::Inactivity`] on a transition.
+    pub fn update_from_pin(&mut self, is_high: bool) -> Option<Event> {
+        let asleep = is_high != self.active_low;
+
+        self.update(if asleep {
+            ActivityState::Inactive
+        } else {
+            ActivityState::Active
+        })
+    }
+
+    /// Update with the time between the last two data-ready samples in
+    /// microseconds. Returns [`Event::Activity`] or [`Event::Inactivity`] on
+    /// a transition. Never reports sleep for data rates of 10Hz and below,
+    /// which do not change when the device sleeps.
+    pub fn update_from_interval(&mut self, interval_us: u32) -> Option<Event> {
+        self.update(if interval_us > self.sleep_interval_us {
+            ActivityState::Inactive
+        } else {
+            ActivityState::Active
+        })
+    }
+
+    fn update(&mut self, state: ActivityState) -> Option<Event> {
+        if self.state == Some(state) {
+            return None;
+        }
+
+        self.state = Some(state);
+
+        Some(state.event())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::register::{Register, H_LACTIVE, I2_ACT};
+    use crate::{Configuration, Error, Lis3dh};
+
+    fn lis3dh() -> Lis3dh<FakeDevice> {
+        let config = Configuration {
+            datarate: DataRate::Hz_100,
+            ..Configuration::default()
+        };
+
+        Lis3dh::new_with_core(FakeDevice::new(), config).unwrap()
+    }
+
+    fn register(lis3dh: &mut Lis3dh<FakeDevice>, register: Register) -> &mut u8 {
+        &mut lis3dh.core_mut().registers[register.addr() as usize]
+    }
+
+    #[test]
+    fn configure() {
+        let mut lis3dh = lis3dh();
+        lis3dh
+            .configure_activity(ActivityConfig {
+                threshold_mg: 250.0,
+                return_to_sleep_s: 5.0,
+                ..ActivityConfig::default()
+            })
+            .unwrap();
+
+        assert_eq!(*register(&mut lis3dh, Register::ACT_THS), 16);
+        // (500 samples - 1) / 8
+        assert_eq!(*register(&mut lis3dh, Register::ACT_DUR), 62);
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), I2_ACT);
+    }
+
+    #[test]
+    fn keeps_the_pin_polarity() {
+        let mut lis3dh = lis3dh();
+        *register(&mut lis3dh, Register::CTRL6) = H_LACTIVE;
+
+        lis3dh
+            .configure_activity(ActivityConfig::default())
+            .unwrap();
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), I2_ACT | H_LACTIVE);
+
+        lis3dh
+            .configure_activity(ActivityConfig {
+                active_low: Some(false),
+                ..ActivityConfig::default()
+            })
+            .unwrap();
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), I2_ACT);
+
+        lis3dh
+            .configure_activity(ActivityConfig {
+                active_low: Some(true),
+                ..ActivityConfig::default()
+            })
+            .unwrap();
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), I2_ACT | H_LACTIVE);
+    }
+
+    #[test]
+    fn rejects_long_durations() {
+        let mut lis3dh = lis3dh();
+        let result = lis3dh.configure_activity(ActivityConfig {
+            return_to_sleep_s: 30.0,
+            ..ActivityConfig::default()
+        });
+
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), 0);
+    }
+
+    #[test]
+    fn update_from_pin() {
+        let mut tracker = ActivityTracker::new(false, DataRate::Hz_400);
+        assert_eq!(tracker.state(), None);
+
+        assert_eq!(tracker.update_from_pin(false), Some(Event::Activity));
+        assert_eq!(tracker.update_from_pin(false), None);
+        assert_eq!(tracker.update_from_pin(true), Some(Event::Inactivity));
+        assert_eq!(tracker.state(), Some(ActivityState::Inactive));
+
+        let mut tracker = ActivityTracker::new(true, DataRate::Hz_400);
+        assert_eq!(tracker.update_from_pin(false), Some(Event::Inactivity));
+        assert_eq!(tracker.update_from_pin(true), Some(Event::Activity));
+    }
+
+    #[test]
+    fn update_from_interval() {
+        // 2500us between samples, sleeping above 5000us
+        let mut tracker = ActivityTracker::new(false, DataRate::Hz_400);
+        assert_eq!(tracker.update_from_interval(2500), Some(Event::Activity));
+        assert_eq!(tracker.update_from_interval(5000), None);
+        assert_eq!(tracker.update_from_interval(5001), Some(Event::Inactivity));
+        assert_eq!(tracker.update_from_interval(100_000), None);
+        assert_eq!(tracker.update_from_interval(2500), Some(Event::Activity));
+    }
+
+    #[test]
+    fn slow_data_rates_never_sleep() {
+        for datarate in [DataRate::Hz_10, DataRate::Hz_1, DataRate::PowerDown] {
+            let mut tracker = ActivityTracker::new(false, datarate);
+            assert_eq!(tracker.update_from_interval(100_000), Some(Event::Activity));
+            assert_eq!(tracker.update_from_interval(u32::MAX), None);
+            assert_eq!(tracker.state(), Some(ActivityState::Active));
+        }
+    }
+}
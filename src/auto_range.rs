@@ -0,0 +1,245 @@
+use accelerometer::vector::{I16x3, I32x3};
+
+use crate::register::{Mode, Range};
+use crate::{ConfigError, Error, Lis3dh, Lis3dhCore};
+
+/// Auto-ranging settings, see [`AutoRange`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AutoRangeConfig {
+    /// Smallest range to step down to, default [`Range::G2`]
+    pub min: Range,
+
+    /// Largest range to step up to, default [`Range::G16`]
+    pub max: Range,
+
+    /// Step up as soon as any axis reaches this percentage of full scale,
+    /// default `90`
+    pub step_up_percent: u8,
+
+    /// Step down once all axes stay below this percentage of the full scale
+    /// of the smaller range, default `40`. Has to be less than
+    /// `step_up_percent`, so the signal does not trigger a step up again in
+    /// the smaller range.
+    pub step_down_percent: u8,
+
+    /// Number of consecutive samples below `step_down_percent` before
+    /// stepping down, default `100`
+    pub quiet_samples: u16,
+}
+
+impl Default for AutoRangeConfig {
+    fn default() -> Self {
+        Self {
+            min: Range::G2,
+            max: Range::G16,
+            step_up_percent: 90,
+            step_down_percent: 40,
+            quiet_samples: 100,
+        }
+    }
+}
+
+/// A sample in milli-g, tagged with the range it was measured at.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangedSample {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub accel_mg: I32x3,
+    pub range: Range,
+}
+
+/// Switches the full-scale range to follow the signal amplitude: steps up
+/// immediately when a sample comes close to full scale, and steps down
+/// again after a quiet period.
+///
+///     // let mut lis3dh = ...
+///     let mut auto_range = AutoRange::new(AutoRangeConfig::default())?;
+///     loop {
+///         while !lis3dh.is_data_ready()? {}
+///         let sample = auto_range.read(&mut lis3dh)?;
+///         // sample.accel_mg was measured at sample.range
+///     }
+#[derive(Debug, Copy, Clone)]
+pub struct AutoRange {
+    config: AutoRangeConfig,
+    quiet: u16,
+}
+
+impl AutoRange {
+    /// Returns [`ConfigError::InvalidAutoRange`] when `min` is larger than
+    /// `max`, or the step thresholds are too close to each other.
+    pub fn new(config: AutoRangeConfig) -> Result<Self, ConfigError> {
+        if index(config.min) > index(config.max)
+            || config.step_up_percent > 100
+            || config.step_down_percent >= config.step_up_percent
+        {
+            return Err(ConfigError::InvalidAutoRange);
+        }
+
+        Ok(Self { config, quiet: 0 })
+    }
+
+    /// Read the current sample and switch the range if needed. The sample is
+    /// tagged with the range it was measured at, a range switch only affects
+    /// the following samples.
+    pub fn read<CORE>(
+        &mut self,
+        lis3dh: &mut Lis3dh<CORE>,
+    ) -> Result<RangedSample, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+    {
+        let (mode, range) = lis3dh.get_mode_and_range()?;
+        let counts = lis3dh.read_counts(mode)?;
+
+        let scale = mode.sensitivity_mg(range) as i32;
+        let sample = RangedSample {
+            accel_mg: I32x3::new(
+                counts.x as i32 * scale,
+                counts.y as i32 * scale,
+                counts.z as i32 * scale,
+            ),
+            range,
+        };
+
+        if let Some(range) = self.update(mode, range, counts) {
+            lis3dh.set_range(range)?;
+        }
+
+        Ok(sample)
+    }
+
+    /// Feed a right-justified sample, see [`Lis3dh::accel_counts`], measured
+    /// in the given mode and range. Returns the range to switch to, if any.
+    pub fn update(&mut self, mode: Mode, range: Range, counts: I16x3) -> Option<Range> {
+        let full_scale = 1u32 << (15 - mode.shift());
+        let peak = [counts.x, counts.y, counts.z]
+            .iter()
+            .map(|count| count.unsigned_abs() as u32)
+            .max()
+            .unwrap_or(0);
+
+        let percent = peak * 100;
+
+        if percent >= full_scale * self.config.step_up_percent as u32 {
+            self.quiet = 0;
+
+            return step_up(range).filter(|up| index(*up) <= index(self.config.max));
+        }
+
+        let down = match step_down(range).filter(|down| index(*down) >= index(self.config.min)) {
+            Some(down) => down,
+            None => {
+                self.quiet = 0;
+                return None;
+            }
+        };
+
+        // Compare in mg against the full scale of the smaller range, G8 to
+        // G16 is not a factor of two
+        let peak_mg = peak * mode.sensitivity_mg(range) as u32;
+        let down_full_scale_mg = full_scale * mode.sensitivity_mg(down) as u32;
+        if peak_mg * 100 >= down_full_scale_mg * self.config.step_down_percent as u32 {
+            self.quiet = 0;
+            return None;
+        }
+
+        self.quiet = self.quiet.saturating_add(1);
+        if self.quiet < self.config.quiet_samples {
+            return None;
+        }
+
+        self.quiet = 0;
+        Some(down)
+    }
+}
+
+const fn index(range: Range) -> u8 {
+    match range {
+        Range::G2 => 0,
+        Range::G4 => 1,
+        Range::G8 => 2,
+        Range::G16 => 3,
+    }
+}
+
+const fn step_up(range: Range) -> Option<Range> {
+    match range {
+        Range::G2 => Some(Range::G4),
+        Range::G4 => Some(Range::G8),
+        Range::G8 => Some(Range::G16),
+        Range::G16 => None,
+    }
+}
+
+const fn step_down(range: Range) -> Option<Range> {
+    match range {
+        Range::G2 => None,
+        Range::G4 => Some(Range::G2),
+        Range::G8 => Some(Range::G4),
+        Range::G16 => Some(Range::G8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_period(auto_range: &mut AutoRange, range: Range, count: i16) -> Option<Range> {
+        let counts = I16x3::new(count, 0, 0);
+        (0..100)
+            .map(|_| auto_range.update(Mode::HighResolution, range, counts))
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn steps_up_near_full_scale() {
+        let mut auto_range = AutoRange::new(AutoRangeConfig::default()).unwrap();
+        let counts = I16x3::new(0, -1900, 0);
+
+        assert_eq!(
+            auto_range.update(Mode::HighResolution, Range::G2, counts),
+            Some(Range::G4)
+        );
+        assert_eq!(
+            auto_range.update(Mode::HighResolution, Range::G16, counts),
+            None
+        );
+    }
+
+    #[test]
+    fn steps_down_below_smaller_full_scale() {
+        let mut auto_range = AutoRange::new(AutoRangeConfig::default()).unwrap();
+
+        // 700 × 12 mg = 8.4 g would clip at G8
+        assert_eq!(quiet_period(&mut auto_range, Range::G16, 700), None);
+        // 270 × 12 mg = 3.24 g, below 40% of 8.19 g
+        assert_eq!(
+            quiet_period(&mut auto_range, Range::G16, 270),
+            Some(Range::G8)
+        );
+        // 500 × 4 mg = 2 g, above 40% of 4.1 g
+        assert_eq!(quiet_period(&mut auto_range, Range::G8, 500), None);
+        // 400 × 4 mg = 1.6 g
+        assert_eq!(
+            quiet_period(&mut auto_range, Range::G8, 400),
+            Some(Range::G4)
+        );
+        assert_eq!(quiet_period(&mut auto_range, Range::G2, 0), None);
+    }
+
+    #[test]
+    fn rejects_overlapping_thresholds() {
+        let config = AutoRangeConfig {
+            step_down_percent: 90,
+            ..AutoRangeConfig::default()
+        };
+
+        assert_eq!(
+            AutoRange::new(config).unwrap_err(),
+            ConfigError::InvalidAutoRange
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use core::convert::TryInto;
+
+use accelerometer::vector::F32x3;
+
+use crate::{ConfigError, Error, Lis3dh, Lis3dhCore, SignedAxis};
+
+/// Offset and gain correction applied to readings in g, in the board frame.
+///
+/// The corrected reading is `matrix * (reading - offset)`. Use
+/// [`SixPositionCalibration`] to determine the values, and
+/// [`Lis3dh::set_calibration`] to apply them to [`Accelerometer::accel_norm`].
+///
+/// [`Accelerometer::accel_norm`]: accelerometer::Accelerometer::accel_norm
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    /// Zero-g offset in g
+    pub offset: [f32; 3],
+
+    /// Gain correction, the diagonal corrects the scale of each axis and the
+    /// other elements the cross-axis sensitivity
+    pub matrix: [[f32; 3]; 3],
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            offset: [0.0; 3],
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+impl Calibration {
+    /// Size of the serialized calibration
+    pub const BYTES: usize = 48;
+
+    /// Correct a reading in g.
+    pub fn apply(&self, reading: F32x3) -> F32x3 {
+        let v = [
+            reading.x - self.offset[0],
+            reading.y - self.offset[1],
+            reading.z - self.offset[2],
+        ];
+        let [x, y, z] = self
+            .matrix
+            .map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2]);
+
+        F32x3::new(x, y, z)
+    }
+
+    /// Serialize as the offset followed by the matrix rows, as little-endian
+    /// `f32`s.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0u8; Self::BYTES];
+
+        let values = self.offset.iter().chain(self.matrix.iter().flatten());
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Deserialize a calibration written by [`Calibration::to_bytes`].
+    /// Returns [`ConfigError::InvalidEncoding`] when a value is not finite.
+    pub fn from_bytes(bytes: &[u8; Self::BYTES]) -> Result<Self, ConfigError> {
+        let mut values = [0.0f32; 12];
+        for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(4)) {
+            *value = f32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err(ConfigError::InvalidEncoding);
+        }
+
+        Ok(Self {
+            offset: [values[0], values[1], values[2]],
+            matrix: [
+                [values[3], values[4], values[5]],
+                [values[6], values[7], values[8]],
+                [values[9], values[10], values[11]],
+            ],
+        })
+    }
+}
+
+/// Collects readings with each board axis pointing up and down in turn, and
+/// solves for a [`Calibration`].
+///
+/// The device has to rest during the collection of each position, the
+/// average of the readings is used.
+///
+///     // let mut lis3dh = ...
+///     let mut collector = SixPositionCalibration::new();
+///     while let Some(position) = collector.next_position() {
+///         // ask the user to put the board with `position` pointing up
+///         collector.collect(&mut lis3dh, position, 100)?;
+///     }
+///     lis3dh.set_calibration(collector.solve(false).unwrap());
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SixPositionCalibration {
+    sums: [[f32; 3]; 6],
+    counts: [u32; 6],
+}
+
+/// Order in which [`SixPositionCalibration::next_position`] asks for the
+/// positions
+const POSITIONS: [SignedAxis; 6] = [
+    SignedAxis::PositiveZ,
+    SignedAxis::NegativeZ,
+    SignedAxis::PositiveX,
+    SignedAxis::NegativeX,
+    SignedAxis::PositiveY,
+    SignedAxis::NegativeY,
+];
+
+impl SixPositionCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The first position without any readings, or `None` when all six
+    /// positions have been collected.
+    pub fn next_position(&self) -> Option<SignedAxis> {
+        POSITIONS
+            .iter()
+            .copied()
+            .find(|position| self.counts[slot(*position)] == 0)
+    }
+
+    /// Have all six positions been collected?
+    pub fn is_complete(&self) -> bool {
+        self.next_position().is_none()
+    }
+
+    /// Add an uncorrected reading in g, taken with the given board axis
+    /// pointing up.
+    pub fn add_reading(&mut self, position: SignedAxis, reading: F32x3) {
+        let slot = slot(position);
+
+        self.sums[slot][0] += reading.x;
+        self.sums[slot][1] += reading.y;
+        self.sums[slot][2] += reading.z;
+        self.counts[slot] += 1;
+    }
+
+    /// Wait for and add `samples` uncorrected readings from the device, with
+    /// the given board axis pointing up.
+    pub fn collect<CORE>(
+        &mut self,
+        lis3dh: &mut Lis3dh<CORE>,
+        position: SignedAxis,
+        samples: u16,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+    {
+        for _ in 0..samples {
+            while !lis3dh.is_data_ready()? {}
+
+            let reading = lis3dh.accel_uncalibrated()?;
+            self.add_reading(position, reading);
+        }
+
+        Ok(())
+    }
+
+    /// Solve for the offset and gain, using the closed-form least squares
+    /// solution for six opposite positions. Without `cross_axis` only the
+    /// scale of each axis is corrected.
+    ///
+    /// Returns `None` unless all positions have been collected, or when the
+    /// readings do not determine a calibration.
+    pub fn solve(&self, cross_axis: bool) -> Option<Calibration> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let mut means = [[0.0f32; 3]; 6];
+        for (mean, (sum, count)) in means.iter_mut().zip(self.sums.iter().zip(&self.counts)) {
+            *mean = sum.map(|s| s / *count as f32);
+        }
+
+        // The offset is the average over all positions, as the true
+        // accelerations cancel out
+        let mut offset = [0.0f32; 3];
+        for (j, offset) in offset.iter_mut().enumerate() {
+            *offset = means.iter().map(|mean| mean[j]).sum::<f32>() / 6.0;
+        }
+
+        // Column i of the sensitivity matrix is half the difference between
+        // the readings with board axis i up and down
+        let mut sensitivity = [[0.0f32; 3]; 3];
+        for (i, axis) in [
+            (SignedAxis::PositiveX, SignedAxis::NegativeX),
+            (SignedAxis::PositiveY, SignedAxis::NegativeY),
+            (SignedAxis::PositiveZ, SignedAxis::NegativeZ),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let up = means[slot(axis.0)];
+            let down = means[slot(axis.1)];
+
+            for (j, row) in sensitivity.iter_mut().enumerate() {
+                if cross_axis || i == j {
+                    row[i] = (up[j] - down[j]) / 2.0;
+                }
+            }
+        }
+
+        Some(Calibration {
+            offset,
+            matrix: invert(sensitivity)?,
+        })
+    }
+}
+
+const fn slot(position: SignedAxis) -> usize {
+    match position {
+        SignedAxis::PositiveX => 0,
+        SignedAxis::NegativeX => 1,
+        SignedAxis::PositiveY => 2,
+        SignedAxis::NegativeY => 3,
+        SignedAxis::PositiveZ => 4,
+        SignedAxis::NegativeZ => 5,
+    }
+}
+
+/// Invert a 3×3 matrix, `None` if it is (nearly) singular.
+fn invert(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+
+    let det = m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2)
+        + m[0][2] * cofactor(1, 2, 0, 1);
+
+    if !det.is_finite() || (-1e-6..1e-6).contains(&det) {
+        return None;
+    }
+
+    // Transposed cofactors divided by the determinant
+    Some([
+        [
+            cofactor(1, 2, 1, 2) / det,
+            -cofactor(0, 2, 1, 2) / det,
+            cofactor(0, 1, 1, 2) / det,
+        ],
+        [
+            -cofactor(1, 2, 0, 2) / det,
+            cofactor(0, 2, 0, 2) / det,
+            -cofactor(0, 1, 0, 2) / det,
+        ],
+        [
+            cofactor(1, 2, 0, 1) / det,
+            -cofactor(0, 2, 0, 1) / det,
+            cofactor(0, 1, 0, 1) / det,
+        ],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFSET: [f32; 3] = [0.05, -0.02, 0.1];
+
+    /// Readings in g per g along each board axis, column by column
+    const SENSITIVITY: [[f32; 3]; 3] = [[1.02, 0.01, 0.0], [0.0, 0.98, -0.02], [0.005, 0.0, 1.01]];
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    /// Collect readings of a device with the given offset and sensitivity,
+    /// with noise that averages out.
+    fn collect(sensitivity: [[f32; 3]; 3]) -> SixPositionCalibration {
+        let mut collector = SixPositionCalibration::new();
+        while let Some(position) = collector.next_position() {
+            // Slots alternate between the positive and negative direction
+            let sign = if slot(position) % 2 == 1 { -1.0 } else { 1.0 };
+            let column = sensitivity.map(|row| row[slot(position) / 2] * sign);
+
+            for noise in [-0.01, 0.01] {
+                let reading = [0, 1, 2].map(|j| OFFSET[j] + column[j] + noise);
+                collector.add_reading(position, F32x3::new(reading[0], reading[1], reading[2]));
+            }
+        }
+
+        collector
+    }
+
+    #[test]
+    fn round_trip() {
+        let calibration = Calibration {
+            offset: OFFSET,
+            matrix: SENSITIVITY,
+        };
+        let bytes = calibration.to_bytes();
+
+        assert_eq!(bytes[..4], 0.05f32.to_le_bytes());
+        assert_eq!(bytes[12..16], 1.02f32.to_le_bytes());
+        assert_eq!(Calibration::from_bytes(&bytes), Ok(calibration));
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let mut bytes = Calibration::default().to_bytes();
+            bytes[20..24].copy_from_slice(&value.to_le_bytes());
+
+            assert_eq!(
+                Calibration::from_bytes(&bytes),
+                Err(ConfigError::InvalidEncoding)
+            );
+        }
+    }
+
+    #[test]
+    fn solves_scale() {
+        let collector = collect(SENSITIVITY);
+        let calibration = collector.solve(false).unwrap();
+
+        for (j, offset) in calibration.offset.iter().enumerate() {
+            assert_close(*offset, OFFSET[j]);
+        }
+        for (i, row) in calibration.matrix.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 / SENSITIVITY[i][i] } else { 0.0 };
+                assert_close(*value, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_cross_axis_sensitivity() {
+        let collector = collect(SENSITIVITY);
+        let calibration = collector.solve(true).unwrap();
+
+        // 1 g along the board y axis
+        let reading = F32x3::new(
+            OFFSET[0] + SENSITIVITY[0][1],
+            OFFSET[1] + SENSITIVITY[1][1],
+            OFFSET[2] + SENSITIVITY[2][1],
+        );
+        let corrected = calibration.apply(reading);
+        assert_close(corrected.x, 0.0);
+        assert_close(corrected.y, 1.0);
+        assert_close(corrected.z, 0.0);
+    }
+
+    #[test]
+    fn requires_all_positions() {
+        let mut collector = SixPositionCalibration::new();
+        for position in POSITIONS[..5].iter() {
+            collector.add_reading(*position, F32x3::new(0.0, 0.0, 1.0));
+        }
+
+        assert_eq!(collector.next_position(), Some(SignedAxis::NegativeY));
+        assert!(collector.solve(false).is_none());
+    }
+
+    #[test]
+    fn rejects_singular_readings() {
+        // A dead z axis
+        let collector = collect([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]);
+        assert!(collector.is_complete());
+
+        assert!(collector.solve(false).is_none());
+        assert!(collector.solve(true).is_none());
+        assert!(invert([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 0.0, 1.0]]).is_none());
+        assert!(invert([[f32::NAN, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]).is_none());
+    }
+
+    #[test]
+    fn inverts() {
+        let inverse = invert([[2.0, 0.0, 0.0], [0.0, 0.0, 4.0], [0.0, -1.0, 0.0]]).unwrap();
+
+        assert_eq!(
+            inverse,
+            [[0.5, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 0.25, 0.0]]
+        );
+    }
+}
@@ -0,0 +1,617 @@
+#[cfg(feature = "std")]
+use core::convert::{TryFrom, TryInto};
+use core::fmt::{self, Debug, Display};
+
+#[cfg(feature = "std")]
+use accelerometer::vector::F32x3;
+use accelerometer::vector::I16x3;
+
+use crate::crc::{crc32, crc32_update};
+use crate::register::{Mode, Range};
+use crate::{ByteSink, Calibration, ConfigError, Configuration, Error, Lis3dh, Lis3dhCore};
+
+// Capture layout, all values little-endian:
+//
+// | Offset | Size | Content                               |
+// |:-------|:-----|:--------------------------------------|
+// | 0      | 4    | `MAGIC`                               |
+// | 4      | 1    | version                               |
+// | 5      | 3    | `Configuration`                       |
+// | 8      | 1    | chip id                               |
+// | 9      | 8    | start time                            |
+// | 17     | 48   | `Calibration`                         |
+// | 65     | 4    | CRC-32 of the preceding bytes         |
+//
+// followed by blocks of samples:
+//
+// | Offset | Size | Content                               |
+// |:-------|:-----|:--------------------------------------|
+// | 0      | 4    | block sequence number                 |
+// | 4      | 1    | scale, mode << 4 | range              |
+// | 5      | 2    | sample count `n`                      |
+// | 7      | 6n   | samples, x, y and z as `i16`          |
+// | 7 + 6n | 4    | CRC-32 of the preceding block bytes   |
+
+const MAGIC: [u8; 4] = *b"L3SC";
+const VERSION: u8 = 1;
+
+/// Size of the encoded header
+const HEADER: usize = 69;
+
+/// Size of a block before the samples
+const BLOCK_PREFIX: usize = 7;
+
+/// The context needed to interpret a capture, written once at its start.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CaptureHeader {
+    pub configuration: Configuration,
+
+    /// `WHO_AM_I` of the device
+    pub chip_id: u8,
+
+    /// Time of the first sample, in a unit of the application's choosing,
+    /// e.g. Unix time in milliseconds
+    pub start_time: u64,
+
+    /// Applied by [`CaptureReader`] to the samples
+    pub calibration: Calibration,
+}
+
+impl CaptureHeader {
+    /// Describe the current state of the device.
+    pub fn from_device<CORE>(
+        lis3dh: &mut Lis3dh<CORE>,
+        start_time: u64,
+    ) -> Result<Self, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+    {
+        Ok(Self {
+            configuration: lis3dh.configuration(),
+            chip_id: lis3dh.get_device_id()?,
+            start_time,
+            calibration: lis3dh.calibration(),
+        })
+    }
+
+    fn to_bytes(self) -> [u8; HEADER] {
+        let mut bytes = [0u8; HEADER];
+
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5..8].copy_from_slice(&self.configuration.to_bytes());
+        bytes[8] = self.chip_id;
+        bytes[9..17].copy_from_slice(&self.start_time.to_le_bytes());
+        bytes[17..65].copy_from_slice(&self.calibration.to_bytes());
+
+        let crc = crc32(&bytes[..65]);
+        bytes[65..].copy_from_slice(&crc.to_le_bytes());
+
+        bytes
+    }
+
+    #[cfg(feature = "std")]
+    fn from_bytes<E>(bytes: &[u8; HEADER]) -> Result<Self, CaptureError<E>> {
+        if bytes[..4] != MAGIC {
+            return Err(CaptureError::NotACapture);
+        }
+        if bytes[4] != VERSION {
+            return Err(CaptureError::UnsupportedVersion(bytes[4]));
+        }
+        if u32::from_le_bytes(bytes[65..].try_into().unwrap()) != crc32(&bytes[..65]) {
+            return Err(CaptureError::Corrupt);
+        }
+
+        Ok(Self {
+            configuration: Configuration::from_bytes(bytes[5..8].try_into().unwrap())?,
+            chip_id: bytes[8],
+            start_time: u64::from_le_bytes(bytes[9..17].try_into().unwrap()),
+            calibration: Calibration::from_bytes(bytes[17..65].try_into().unwrap())?,
+        })
+    }
+}
+
+/// Errors while reading a capture.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureError<E> {
+    /// Error of the underlying reader
+    Io(E),
+
+    /// The data does not start with a capture header
+    NotACapture,
+
+    /// The capture was written by a newer version of this crate
+    UnsupportedVersion(u8),
+
+    /// The header or a block does not match its checksum
+    Corrupt,
+
+    /// The capture ends within a block
+    Truncated,
+
+    /// The configuration in the header is invalid
+    InvalidConfiguration(ConfigError),
+}
+
+impl<E> Display for CaptureError<E>
+where
+    E: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::Io(error) => write!(f, "read error: {:?}", error),
+            CaptureError::NotACapture => f.write_str("not a sample capture"),
+            CaptureError::UnsupportedVersion(version) => {
+                write!(f, "unsupported capture version {}", version)
+            }
+            CaptureError::Corrupt => f.write_str("capture is corrupt"),
+            CaptureError::Truncated => f.write_str("capture ends within a block"),
+            CaptureError::InvalidConfiguration(error) => {
+                write!(f, "invalid captured configuration: {}", error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for CaptureError<E> where E: Debug {}
+
+impl<E> From<ConfigError> for CaptureError<E> {
+    fn from(error: ConfigError) -> Self {
+        CaptureError::InvalidConfiguration(error)
+    }
+}
+
+const fn encode_scale(mode: Mode, range: Range) -> u8 {
+    let mode = match mode {
+        Mode::HighResolution => 0,
+        Mode::Normal => 1,
+        Mode::LowPower => 2,
+    };
+
+    mode << 4 | range.bits()
+}
+
+#[cfg(feature = "std")]
+fn decode_scale(scale: u8) -> Option<(Mode, Range)> {
+    let mode = match scale >> 4 {
+        0 => Mode::HighResolution,
+        1 => Mode::Normal,
+        2 => Mode::LowPower,
+        _ => return None,
+    };
+    let range = Range::try_from(scale & 0x0F).ok()?;
+
+    Some((mode, range))
+}
+
+/// Writes a capture of samples to a [`ByteSink`], in blocks of up to `N`
+/// samples. Each block records the operating mode and range of its samples,
+/// so they can be interpreted after changes like the ones of
+/// [`AutoRange`](crate::AutoRange).
+///
+///     let header = CaptureHeader::from_device(&mut lis3dh, now_ms())?;
+///     let mut capture = CaptureWriter::<_, 64>::new(file, &header)?;
+///     loop {
+///         let (mode, range) = (lis3dh.get_mode()?, lis3dh.get_range()?);
+///         capture.push(mode, range, lis3dh.accel_counts()?)?;
+///     }
+///
+/// `N` has to be between 1 and 65535, other sizes fail to compile.
+pub struct CaptureWriter<W, const N: usize>
+where
+    W: ByteSink,
+{
+    writer: W,
+    sequence: u32,
+    scale: u8,
+    samples: [[u8; 6]; N],
+    len: usize,
+}
+
+impl<W, const N: usize> CaptureWriter<W, N>
+where
+    W: ByteSink,
+{
+    /// Evaluated by [`CaptureWriter::new`], as the sample count of a block
+    /// is a `u16`
+    const VALID_SIZE: () = assert!(N >= 1 && N <= u16::MAX as usize, "invalid block size");
+
+    /// Start a capture, writing the header.
+    pub fn new(mut writer: W, header: &CaptureHeader) -> Result<Self, W::Error> {
+        let () = Self::VALID_SIZE;
+
+        writer.write_bytes(&header.to_bytes())?;
+
+        Ok(Self {
+            writer,
+            sequence: 0,
+            scale: 0,
+            samples: [[0; 6]; N],
+            len: 0,
+        })
+    }
+
+    /// Add a sample as returned by [`Lis3dh::accel_counts`], read in the
+    /// given mode and range. Writes a block when it is full or the mode or
+    /// range changed.
+    pub fn push(&mut self, mode: Mode, range: Range, counts: I16x3) -> Result<(), W::Error> {
+        let scale = encode_scale(mode, range);
+        if self.len > 0 && scale != self.scale {
+            self.flush()?;
+        }
+        self.scale = scale;
+
+        let sample = &mut self.samples[self.len];
+        sample[0..2].copy_from_slice(&counts.x.to_le_bytes());
+        sample[2..4].copy_from_slice(&counts.y.to_le_bytes());
+        sample[4..6].copy_from_slice(&counts.z.to_le_bytes());
+        self.len += 1;
+
+        if self.len == N {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the pending samples as a block.
+    pub fn flush(&mut self) -> Result<(), W::Error> {
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        let mut prefix = [0u8; BLOCK_PREFIX];
+        prefix[..4].copy_from_slice(&self.sequence.to_le_bytes());
+        prefix[4] = self.scale;
+        prefix[5..].copy_from_slice(&(self.len as u16).to_le_bytes());
+
+        self.writer.write_bytes(&prefix)?;
+        let mut crc = crc32_update(!0, &prefix);
+        for sample in &self.samples[..self.len] {
+            self.writer.write_bytes(sample)?;
+            crc = crc32_update(crc, sample);
+        }
+        self.writer.write_bytes(&(!crc).to_le_bytes())?;
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.len = 0;
+
+        Ok(())
+    }
+
+    /// Write the pending samples and return the writer.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        self.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reads a capture written by [`CaptureWriter`], yielding the samples in g
+/// with the calibration of the header applied.
+///
+///     let mut capture = CaptureReader::new(std::fs::File::open("capture.l3sc")?)?;
+///     println!("{:?}", capture.header().configuration.datarate);
+///     for sample in &mut capture {
+///         let sample = sample?;
+///         println!("{} {} {}", sample.x, sample.y, sample.z);
+///     }
+///
+/// Missing blocks are skipped and counted, see
+/// [`CaptureReader::missing_blocks`]. Iteration ends after the first error.
+#[cfg(feature = "std")]
+pub struct CaptureReader<R> {
+    reader: R,
+    header: CaptureHeader,
+
+    /// Sequence number of the next block
+    sequence: Option<u32>,
+    missing_blocks: u32,
+
+    samples: std::vec::Vec<u8>,
+    position: usize,
+    scale: f32,
+    failed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R> CaptureReader<R>
+where
+    R: std::io::Read,
+{
+    /// Read the header of a capture.
+    pub fn new(mut reader: R) -> Result<Self, CaptureError<std::io::Error>> {
+        let mut bytes = [0u8; HEADER];
+        if !read_exact_or_end(&mut reader, &mut bytes)? {
+            return Err(CaptureError::NotACapture);
+        }
+
+        Ok(Self {
+            reader,
+            header: CaptureHeader::from_bytes(&bytes)?,
+            sequence: None,
+            missing_blocks: 0,
+            samples: std::vec::Vec::new(),
+            position: 0,
+            scale: 0.0,
+            failed: false,
+        })
+    }
+
+    pub fn header(&self) -> &CaptureHeader {
+        &self.header
+    }
+
+    /// Number of blocks missing from the sequence so far.
+    pub fn missing_blocks(&self) -> u32 {
+        self.missing_blocks
+    }
+
+    /// Read the next block, `false` at the end of the capture.
+    fn read_block(&mut self) -> Result<bool, CaptureError<std::io::Error>> {
+        let mut prefix = [0u8; BLOCK_PREFIX];
+        if !read_exact_or_end(&mut self.reader, &mut prefix)? {
+            return Ok(false);
+        }
+
+        let len = u16::from_le_bytes([prefix[5], prefix[6]]) as usize;
+        self.samples.resize(6 * len + 4, 0);
+        if !read_exact_or_end(&mut self.reader, &mut self.samples)? {
+            return Err(CaptureError::Truncated);
+        }
+
+        let crc_bytes = self.samples.split_off(6 * len);
+        let crc = !crc32_update(crc32_update(!0, &prefix), &self.samples);
+        if crc_bytes[..] != crc.to_le_bytes() {
+            return Err(CaptureError::Corrupt);
+        }
+
+        let (mode, range) = decode_scale(prefix[4]).ok_or(CaptureError::Corrupt)?;
+        self.scale = mode.sensitivity_mg(range) as f32 / 1000.0;
+        self.position = 0;
+
+        let sequence = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+        if let Some(expected) = self.sequence {
+            self.missing_blocks = self
+                .missing_blocks
+                .wrapping_add(sequence.wrapping_sub(expected));
+        }
+        self.sequence = Some(sequence.wrapping_add(1));
+
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> Iterator for CaptureReader<R>
+where
+    R: std::io::Read,
+{
+    type Item = Result<F32x3, CaptureError<std::io::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed && self.position == self.samples.len() {
+            match self.read_block() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        if self.failed {
+            return None;
+        }
+
+        let sample = &self.samples[self.position..self.position + 6];
+        self.position += 6;
+
+        let count = |i: usize| i16::from_le_bytes([sample[i], sample[i + 1]]) as f32 * self.scale;
+        let g = F32x3::new(count(0), count(2), count(4));
+
+        Some(Ok(self.header.calibration.apply(g)))
+    }
+}
+
+/// Fill `buf`, returning `false` if the reader ended before the first byte.
+#[cfg(feature = "std")]
+fn read_exact_or_end<R>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<bool, CaptureError<std::io::Error>>
+where
+    R: std::io::Read,
+{
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(CaptureError::Truncated),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(CaptureError::Io(error)),
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RecordBuffer;
+
+    fn header() -> CaptureHeader {
+        CaptureHeader {
+            configuration: Configuration::default(),
+            chip_id: 0x33,
+            start_time: 1_700_000_000_000,
+            calibration: Calibration {
+                offset: [0.5, 0.0, 0.0],
+                ..Calibration::default()
+            },
+        }
+    }
+
+    /// A capture with blocks of two samples: two in high-resolution ±2 g, one
+    /// in normal ±4 g.
+    fn capture() -> RecordBuffer<256> {
+        let mut writer = CaptureWriter::<_, 2>::new(RecordBuffer::new(), &header()).unwrap();
+        for x in [1000, 2000] {
+            writer
+                .push(Mode::HighResolution, Range::G2, I16x3::new(x, 0, -1000))
+                .unwrap();
+        }
+        writer
+            .push(Mode::Normal, Range::G4, I16x3::new(125, 0, 0))
+            .unwrap();
+
+        writer.finish().unwrap()
+    }
+
+    /// Offset of the block with the given index in [`capture`].
+    fn block(index: usize) -> usize {
+        HEADER + index * (BLOCK_PREFIX + 2 * 6 + 4)
+    }
+
+    #[test]
+    fn header_layout() {
+        let capture = capture();
+        let bytes = capture.as_bytes();
+
+        assert_eq!(bytes[..5], *b"L3SC\x01");
+        assert_eq!(bytes[5..8], Configuration::default().to_bytes());
+        assert_eq!(bytes[8], 0x33);
+        assert_eq!(bytes[9..17], 1_700_000_000_000u64.to_le_bytes());
+        assert_eq!(bytes[17..21], 0.5f32.to_le_bytes());
+        assert_eq!(bytes[65..69], crc32(&bytes[..65]).to_le_bytes());
+    }
+
+    #[test]
+    fn block_layout() {
+        let capture = capture();
+        let bytes = capture.as_bytes();
+
+        let first = &bytes[block(0)..block(1)];
+        assert_eq!(first[..7], [0, 0, 0, 0, 0x00, 2, 0]);
+        assert_eq!(first[7..13], [0xE8, 0x03, 0, 0, 0x18, 0xFC]);
+        assert_eq!(first[19..], crc32(&first[..19]).to_le_bytes());
+
+        // Flushed by the change of mode and range
+        let second = &bytes[block(1)..];
+        assert_eq!(second.len(), BLOCK_PREFIX + 6 + 4);
+        assert_eq!(second[..7], [1, 0, 0, 0, 0x11, 1, 0]);
+        assert_eq!(second[13..], crc32(&second[..13]).to_le_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    type Samples = std::vec::Vec<Result<F32x3, CaptureError<std::io::Error>>>;
+
+    #[cfg(feature = "std")]
+    fn read(bytes: &[u8]) -> (CaptureReader<&[u8]>, Samples) {
+        let mut reader = CaptureReader::new(bytes).unwrap();
+        let samples = (&mut reader).collect();
+
+        (reader, samples)
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reads_calibrated_samples() {
+        let capture = capture();
+        let (reader, samples) = read(capture.as_bytes());
+
+        assert_eq!(*reader.header(), header());
+        assert_eq!(reader.missing_blocks(), 0);
+
+        let samples: std::vec::Vec<_> = samples
+            .into_iter()
+            .map(|sample| {
+                let sample = sample.unwrap();
+                (sample.x, sample.y, sample.z)
+            })
+            .collect();
+        assert_eq!(
+            samples,
+            [(0.5, 0.0, -1.0), (1.5, 0.0, -1.0), (0.5, 0.0, 0.0)]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn counts_missing_blocks() {
+        let mut writer = CaptureWriter::<_, 1>::new(RecordBuffer::<256>::new(), &header()).unwrap();
+        for x in 0..3 {
+            writer
+                .push(Mode::HighResolution, Range::G2, I16x3::new(x, 0, 0))
+                .unwrap();
+        }
+        let capture = writer.finish().unwrap();
+
+        // Drop the second block
+        let block = BLOCK_PREFIX + 6 + 4;
+        let mut bytes = capture.as_bytes()[..HEADER + block].to_vec();
+        bytes.extend_from_slice(&capture.as_bytes()[HEADER + 2 * block..]);
+
+        let (reader, samples) = read(&bytes);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(reader.missing_blocks(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn detects_corrupt_and_truncated_blocks() {
+        let capture = capture();
+
+        let mut corrupt = capture.as_bytes().to_vec();
+        corrupt[block(1) + 8] ^= 0x01;
+        let (_, samples) = read(&corrupt);
+        assert_eq!(samples.len(), 3);
+        assert!(matches!(samples[2], Err(CaptureError::Corrupt)));
+
+        let truncated = &capture.as_bytes()[..capture.as_bytes().len() - 1];
+        let (_, samples) = read(truncated);
+        assert_eq!(samples.len(), 3);
+        assert!(matches!(samples[2], Err(CaptureError::Truncated)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rejects_invalid_headers() {
+        let capture = capture();
+
+        assert!(matches!(
+            CaptureReader::new(&b""[..]),
+            Err(CaptureError::NotACapture)
+        ));
+        assert!(matches!(
+            CaptureReader::new(&b"L3SC"[..]),
+            Err(CaptureError::Truncated)
+        ));
+
+        let mut magic = capture.as_bytes().to_vec();
+        magic[0] = b'X';
+        assert!(matches!(
+            CaptureReader::new(&magic[..]),
+            Err(CaptureError::NotACapture)
+        ));
+
+        let mut corrupt = capture.as_bytes().to_vec();
+        corrupt[9] ^= 0x01;
+        assert!(matches!(
+            CaptureReader::new(&corrupt[..]),
+            Err(CaptureError::Corrupt)
+        ));
+
+        let mut version = capture.as_bytes().to_vec();
+        version[4] = 2;
+        assert!(matches!(
+            CaptureReader::new(&version[..]),
+            Err(CaptureError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+/// CRC-32 (IEEE 802.3, as used by zlib and PNG), computed bitwise to avoid a
+/// lookup table in flash.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(!0, data)
+}
+
+/// Continue a CRC-32 over `data`. Start with `!0` and invert the result.
+pub(crate) fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn update_in_parts() {
+        let crc = crc32_update(crc32_update(!0, b"1234"), b"56789");
+
+        assert_eq!(!crc, crc32(b"123456789"));
+    }
+}
@@ -0,0 +1,301 @@
+use accelerometer::vector::I16x3;
+use embedded_hal::digital::v2::InputPin;
+
+use crate::register::*;
+use crate::{Error, Lis3dh, Lis3dhCore};
+
+/// Marker for a [`DataReady`] reader without an interrupt pin. New data is
+/// detected by polling `STATUS_REG` instead.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoPin;
+
+/// Errors of the [`DataReady`] reader.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataReadyError<BusError, PinError, IrqPinError> {
+    /// Error communicating with the device
+    Device(Error<BusError, PinError>),
+
+    /// Error reading the data-ready pin
+    IrqPin(IrqPinError),
+}
+
+type DataReadyResult<T, CORE, IrqPinError> = Result<
+    T,
+    DataReadyError<<CORE as Lis3dhCore>::BusError, <CORE as Lis3dhCore>::PinError, IrqPinError>,
+>;
+
+impl<BusError, PinError, IrqPinError> From<Error<BusError, PinError>>
+    for DataReadyError<BusError, PinError, IrqPinError>
+{
+    fn from(error: Error<BusError, PinError>) -> Self {
+        DataReadyError::Device(error)
+    }
+}
+
+/// Reads every sample exactly once, as soon as the device signals that new
+/// data is available.
+///
+/// With a pin, the data-ready signal (`I1_ZYXDA`) is routed to the `INT1` pin
+/// and the reader waits for the pin to be asserted. The signal is cleared by
+/// reading the sample, so every output data rate tick yields one sample.
+///
+///     // let lis3dh = ...
+///     // let int1 = pins.p0_25.into_floating_input();
+///     let mut reader = DataReady::new(lis3dh, int1)?;
+///
+///     loop {
+///         let sample = reader.next_sample()?;
+///     }
+///
+/// Without a pin, use [`DataReady::polling`] to wait on `STATUS_REG` instead.
+pub struct DataReady<CORE, PIN> {
+    lis3dh: Lis3dh<CORE>,
+    pin: PIN,
+
+    /// `H_LACTIVE`, `None` when it has to be read again
+    active_low: Option<bool>,
+}
+
+impl<CORE, PIN> DataReady<CORE, PIN>
+where
+    CORE: Lis3dhCore,
+{
+    /// Route the data-ready signal to the `INT1` pin and wait on `pin` for
+    /// new data. The other interrupts routed to `INT1` are left untouched.
+    /// The pin polarity is taken from the `H_LACTIVE` bit in `CTRL_REG6`.
+    #[doc(alias = "I1_ZYXDA")]
+    pub fn new(
+        mut lis3dh: Lis3dh<CORE>,
+        pin: PIN,
+    ) -> Result<Self, Error<CORE::BusError, CORE::PinError>> {
+        lis3dh.register_set_bits(Register::CTRL3, I1_ZYXDA)?;
+        let active_low = lis3dh.read_register(Register::CTRL6)? & H_LACTIVE != 0;
+
+        Ok(Self {
+            lis3dh,
+            pin,
+            active_low: Some(active_low),
+        })
+    }
+
+    /// Access the driver, e.g. to change the configuration between samples.
+    /// The pin polarity is read again before waiting for the next sample, so
+    /// it may be changed as well. Do not unroute the data-ready signal from
+    /// `INT1`.
+    pub fn lis3dh(&mut self) -> &mut Lis3dh<CORE> {
+        self.active_low = None;
+
+        &mut self.lis3dh
+    }
+
+    /// Destroy the reader and return the driver and the pin. The data-ready
+    /// signal stays routed to `INT1`.
+    pub fn release(self) -> (Lis3dh<CORE>, PIN) {
+        (self.lis3dh, self.pin)
+    }
+
+    /// Read the current sample, discarding it if the outputs have not
+    /// settled yet.
+    fn read_settled<IrqPinError>(&mut self) -> DataReadyResult<Option<I16x3>, CORE, IrqPinError> {
+        let settled = self.lis3dh.is_settled();
+        let sample = self.lis3dh.read_accel_raw()?;
+
+        Ok(if settled { Some(sample) } else { None })
+    }
+
+    /// The pin polarity, read again after the driver was accessed through
+    /// [`DataReady::lis3dh`].
+    fn active_low(&mut self) -> Result<bool, Error<CORE::BusError, CORE::PinError>> {
+        match self.active_low {
+            Some(active_low) => Ok(active_low),
+            None => {
+                let active_low = self.lis3dh.read_register(Register::CTRL6)? & H_LACTIVE != 0;
+                self.active_low = Some(active_low);
+
+                Ok(active_low)
+            }
+        }
+    }
+}
+
+impl<CORE> DataReady<CORE, NoPin>
+where
+    CORE: Lis3dhCore,
+{
+    /// Create a reader that polls `STATUS_REG` for new data.
+    pub fn polling(lis3dh: Lis3dh<CORE>) -> Self {
+        Self {
+            lis3dh,
+            pin: NoPin,
+            active_low: None,
+        }
+    }
+
+    /// Block until a new sample is available and return it. See
+    /// [`RawAccelerometer::accel_raw`](accelerometer::RawAccelerometer::accel_raw).
+    pub fn next_sample(&mut self) -> DataReadyResult<I16x3, CORE, core::convert::Infallible> {
+        loop {
+            while !self.lis3dh.is_data_ready()? {}
+
+            if let Some(sample) = self.read_settled()? {
+                return Ok(sample);
+            }
+        }
+    }
+}
+
+impl<CORE, PIN> DataReady<CORE, PIN>
+where
+    CORE: Lis3dhCore,
+    PIN: InputPin,
+{
+    /// Block until the data-ready pin is asserted and return the new sample.
+    /// See [`RawAccelerometer::accel_raw`](accelerometer::RawAccelerometer::accel_raw).
+    pub fn next_sample(&mut self) -> DataReadyResult<I16x3, CORE, PIN::Error> {
+        let active_low = self.active_low()?;
+
+        loop {
+            while !self.is_asserted(active_low)? {}
+
+            if let Some(sample) = self.read_settled()? {
+                return Ok(sample);
+            }
+        }
+    }
+
+    fn is_asserted(&self, active_low: bool) -> DataReadyResult<bool, CORE, PIN::Error> {
+        let level = if active_low {
+            self.pin.is_low()
+        } else {
+            self.pin.is_high()
+        };
+
+        level.map_err(DataReadyError::IrqPin)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<CORE, PIN> DataReady<CORE, PIN>
+where
+    CORE: Lis3dhCore,
+    PIN: embedded_hal_async::digital::Wait,
+{
+    /// Wait for the data-ready pin to be asserted and return the new sample.
+    pub async fn next_sample_async(&mut self) -> DataReadyResult<I16x3, CORE, PIN::Error> {
+        let active_low = self.active_low()?;
+
+        loop {
+            let level = if active_low {
+                self.pin.wait_for_low().await
+            } else {
+                self.pin.wait_for_high().await
+            };
+            level.map_err(DataReadyError::IrqPin)?;
+
+            if let Some(sample) = self.read_settled()? {
+                return Ok(sample);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::{Configuration, IrqPin2Config};
+
+    /// A pin stuck at one level, failing the test instead of waiting forever
+    struct Pin {
+        high: bool,
+        polls: Cell<u32>,
+    }
+
+    impl Pin {
+        fn new(high: bool) -> Self {
+            Self {
+                high,
+                polls: Cell::new(0),
+            }
+        }
+
+        fn level(&self) -> bool {
+            self.polls.set(self.polls.get() + 1);
+            assert!(self.polls.get() < 100, "pin is never asserted");
+
+            self.high
+        }
+    }
+
+    impl InputPin for Pin {
+        type Error = core::convert::Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.level())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.level())
+        }
+    }
+
+    fn lis3dh() -> Lis3dh<FakeDevice> {
+        let mut device = FakeDevice::new();
+        device.registers[Register::OUT_X_L.addr() as usize..][..6]
+            .copy_from_slice(&[0x10, 0x00, 0x20, 0x00, 0x30, 0x00]);
+
+        Lis3dh::new_with_core(device, Configuration::default()).unwrap()
+    }
+
+    #[test]
+    fn routes_data_ready_to_int1() {
+        let reader = DataReady::new(lis3dh(), Pin::new(true)).unwrap();
+        let (lis3dh, _) = reader.release();
+
+        assert_eq!(
+            lis3dh.core().registers[Register::CTRL3.addr() as usize],
+            I1_ZYXDA
+        );
+    }
+
+    #[test]
+    fn discards_unsettled_samples() {
+        let mut reader = DataReady::new(lis3dh(), Pin::new(true)).unwrap();
+        reader.lis3dh().core_mut().queue_samples(10);
+
+        assert_eq!(reader.next_sample().unwrap(), I16x3::new(0x10, 0x20, 0x30));
+
+        // Seven samples to settle, and the one returned
+        let (lis3dh, pin) = reader.release();
+        assert_eq!(lis3dh.core().pending, 1);
+        assert_eq!(pin.polls.get(), 8);
+    }
+
+    #[test]
+    fn follows_polarity_changes() {
+        let mut reader = DataReady::new(lis3dh(), Pin::new(false)).unwrap();
+        reader
+            .lis3dh()
+            .configure_interrupt_pin(IrqPin2Config {
+                active_low: true,
+                ..IrqPin2Config::default()
+            })
+            .unwrap();
+        reader.lis3dh().core_mut().queue_samples(8);
+
+        assert_eq!(reader.next_sample().unwrap(), I16x3::new(0x10, 0x20, 0x30));
+    }
+
+    #[test]
+    fn polls_status() {
+        let mut reader = DataReady::polling(lis3dh());
+        reader.lis3dh().core_mut().queue_samples(8);
+
+        assert_eq!(reader.next_sample().unwrap(), I16x3::new(0x10, 0x20, 0x30));
+        assert_eq!(reader.lis3dh().core().pending, 0);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::interrupts::{ClickSource, InterruptMode, InterruptSource};
+use crate::orientation::Orientation;
+use crate::register::*;
+
+/// An interrupt event, see [`Lis3dh::poll_events`](crate::Lis3dh::poll_events).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// New X, Y and Z-axis data is available (`ZYXDA`)
+    DataReady,
+
+    /// X, Y and Z-axis data was overwritten before it was read (`ZYXOR`)
+    DataOverrun,
+
+    /// An interrupt generator in `OrCombination`, `AndCombination` or
+    /// `Movement` mode fired. The source tells which axis events caused it.
+    Motion(InterruptSource),
+
+    /// An interrupt generator configured for an AND combination of low events
+    /// fired, e.g. by [`InterruptConfig::low`](crate::InterruptConfig::low).
+    FreeFall,
+
+    /// An interrupt generator in `Position` mode recognised an orientation
+    Position(Orientation),
+
+    /// Single click detected
+    Click(ClickSource),
+
+    /// Double click detected
+    DoubleClick(ClickSource),
+
+    /// The FIFO reached its watermark level
+    FifoWatermark,
+
+    /// The FIFO is full and samples are being overwritten
+    FifoOverrun,
+
+    /// The device woke up. The activity state is not visible in the source
+    /// registers, this event is reported by an
+    /// [`ActivityTracker`](crate::ActivityTracker).
+    Activity,
+
+    /// The device went to sleep, see [`Event::Activity`].
+    Inactivity,
+}
+
+/// Snapshot of all interrupt source registers, read by
+/// [`Lis3dh::poll_events`](crate::Lis3dh::poll_events). Iterate over it to
+/// get the pending [`Event`]s.
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Events {
+    pub(crate) status: u8,
+    pub(crate) fifo_src: u8,
+    pub(crate) int1_cfg: u8,
+    pub(crate) int1_src: u8,
+    pub(crate) int2_cfg: u8,
+    pub(crate) int2_src: u8,
+    pub(crate) click_src: u8,
+}
+
+impl Events {
+    /// Iterate over the pending events.
+    pub fn iter(&self) -> EventIter {
+        EventIter {
+            events: *self,
+            next: 0,
+        }
+    }
+
+    /// Are there no pending events?
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    fn event(&self, index: u8) -> Option<Event> {
+        match index {
+            0 if self.status & ZYXDA != 0 => Some(Event::DataReady),
+            1 if self.status & ZYXOR != 0 => Some(Event::DataOverrun),
+            2 => interrupt_event(self.int1_cfg, self.int1_src),
+            3 => interrupt_event(self.int2_cfg, self.int2_src),
+            4 => {
+                let click = ClickSource::from_bits(self.click_src);
+                if !click.interrupt_active {
+                    None
+                } else if click.double_click {
+                    Some(Event::DoubleClick(click))
+                } else if click.single_click {
+                    Some(Event::Click(click))
+                } else {
+                    None
+                }
+            }
+            5 if self.fifo_src & WTM != 0 => Some(Event::FifoWatermark),
+            6 if self.fifo_src & OVRN_FIFO != 0 => Some(Event::FifoOverrun),
+            _ => None,
+        }
+    }
+}
+
+impl IntoIterator for Events {
+    type Item = Event;
+    type IntoIter = EventIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &Events {
+    type Item = Event;
+    type IntoIter = EventIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the pending [`Event`]s of an [`Events`] snapshot.
+#[derive(Clone, Debug)]
+pub struct EventIter {
+    events: Events,
+    next: u8,
+}
+
+const EVENT_SLOTS: u8 = 7;
+
+impl Iterator for EventIter {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        while self.next < EVENT_SLOTS {
+            let index = self.next;
+            self.next += 1;
+
+            if let Some(event) = self.events.event(index) {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+}
+
+/// Classify an interrupt generator event from its `INTx_CFG` and `INTx_SRC`
+/// registers.
+fn interrupt_event(cfg: u8, src: u8) -> Option<Event> {
+    let source = InterruptSource::from_bits(src);
+    if !source.interrupt_active {
+        return None;
+    }
+
+    const HIGH_EVENTS: u8 = 0b0010_1010;
+    const LOW_EVENTS: u8 = 0b0001_0101;
+
+    match InterruptMode::from(cfg) {
+        InterruptMode::Position => Orientation::from_source(source).map(Event::Position),
+        InterruptMode::AndCombination if cfg & HIGH_EVENTS == 0 && cfg & LOW_EVENTS != 0 => {
+            Some(Event::FreeFall)
+        }
+        _ => Some(Event::Motion(source)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::{Configuration, Lis3dh};
+
+    const IA: u8 = 0x40;
+    const AND: u8 = 0x80;
+    const POSITION: u8 = 0xC0;
+
+    fn events(events: Events) -> ([Option<Event>; 8], usize) {
+        let mut list = [None; 8];
+        let mut count = 0;
+        for (slot, event) in list.iter_mut().zip(events) {
+            *slot = Some(event);
+            count += 1;
+        }
+
+        (list, count)
+    }
+
+    fn interrupt(cfg: u8, src: u8) -> Events {
+        Events {
+            int1_cfg: cfg,
+            int1_src: src,
+            ..Events::default()
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Events::default().is_empty());
+
+        // axis events without IA
+        assert!(interrupt(0b0011_1111, 0b0011_1111).is_empty());
+    }
+
+    #[test]
+    fn in_order() {
+        let all = Events {
+            status: ZYXDA | ZYXOR,
+            fifo_src: WTM | OVRN_FIFO,
+            int1_cfg: 0b0000_0010,
+            int1_src: IA | 0b0000_0010,
+            int2_cfg: AND | 0b0001_0101,
+            int2_src: IA | 0b0001_0101,
+            click_src: IA | 0b0001_0001,
+        };
+
+        let (list, count) = events(all);
+        assert_eq!(count, 7);
+        assert_eq!(
+            list[..count],
+            [
+                Some(Event::DataReady),
+                Some(Event::DataOverrun),
+                Some(Event::Motion(InterruptSource::from_bits(IA | 0b0000_0010))),
+                Some(Event::FreeFall),
+                Some(Event::Click(ClickSource::from_bits(IA | 0b0001_0001))),
+                Some(Event::FifoWatermark),
+                Some(Event::FifoOverrun),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_fall_requires_and_of_low_events() {
+        // AND of low events
+        assert_eq!(
+            interrupt_event(AND | 0b0001_0101, IA | 0b0001_0101),
+            Some(Event::FreeFall)
+        );
+        assert_eq!(
+            interrupt_event(AND | 0b0000_0001, IA),
+            Some(Event::FreeFall)
+        );
+
+        // OR of low events
+        let source = InterruptSource::from_bits(IA | 0b0000_0100);
+        assert_eq!(
+            interrupt_event(0b0001_0101, IA | 0b0000_0100),
+            Some(Event::Motion(source))
+        );
+
+        // AND including high events
+        let source = InterruptSource::from_bits(IA | 0b0000_0011);
+        assert_eq!(
+            interrupt_event(AND | 0b0000_0011, IA | 0b0000_0011),
+            Some(Event::Motion(source))
+        );
+
+        // AND without any enabled event
+        assert_eq!(
+            interrupt_event(AND, IA),
+            Some(Event::Motion(InterruptSource::from_bits(IA)))
+        );
+
+        assert_eq!(interrupt_event(AND | 0b0001_0101, 0b0001_0101), None);
+    }
+
+    #[test]
+    fn position() {
+        assert_eq!(
+            interrupt_event(POSITION | 0b0011_1111, IA | 0b0010_0000),
+            Some(Event::Position(Orientation::FaceUp))
+        );
+
+        // no orientation recognised
+        assert_eq!(interrupt_event(POSITION | 0b0011_1111, IA), None);
+    }
+
+    #[test]
+    fn double_click_takes_precedence() {
+        let click = |src| {
+            events(Events {
+                click_src: src,
+                ..Events::default()
+            })
+        };
+
+        let single = IA | 0b0001_1100;
+        assert_eq!(click(single).1, 1);
+        assert_eq!(
+            click(single).0[0],
+            Some(Event::Click(ClickSource::from_bits(single)))
+        );
+
+        let double = IA | 0b0011_0001;
+        assert_eq!(click(double).1, 1);
+        assert_eq!(
+            click(double).0[0],
+            Some(Event::DoubleClick(ClickSource::from_bits(double)))
+        );
+
+        // neither click type flagged, or IA not set
+        assert_eq!(click(IA | 0b0000_0001).1, 0);
+        assert_eq!(click(0b0001_0001).1, 0);
+    }
+
+    #[test]
+    fn poll_events() {
+        let mut lis3dh =
+            Lis3dh::new_with_core(FakeDevice::new(), Configuration::default()).unwrap();
+        lis3dh.core_mut().new_sample();
+
+        let registers = &mut lis3dh.core_mut().registers;
+        registers[Register::FIFO_SRC.addr() as usize] = WTM;
+        registers[Register::INT2_CFG.addr() as usize] = AND | 0b0001_0101;
+        registers[Register::INT2_SRC.addr() as usize] = IA | 0b0001_0101;
+
+        let (list, count) = events(lis3dh.poll_events().unwrap());
+        assert_eq!(
+            list[..count],
+            [
+                Some(Event::DataReady),
+                Some(Event::FreeFall),
+                Some(Event::FifoWatermark),
+            ]
+        );
+
+        // no sample was consumed
+        assert!(lis3dh.is_data_ready().unwrap());
+    }
+}
@@ -0,0 +1,351 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::vec::Vec;
+
+use accelerometer::vector::F32x3;
+
+use crate::register::{DataRate, Mode, Range};
+use crate::{CaptureHeader, Error, Lis3dh, Lis3dhCore};
+
+/// Writes samples in g, e.g. from [`Accelerometer::accel_norm`] or a
+/// [`CaptureReader`](crate::CaptureReader), as CSV, JSON Lines or WAV for
+/// analysis in other tools. Sample times are derived from the data rate.
+///
+///     // let mut lis3dh = ...
+///     let export = Export::from_device(&mut lis3dh)?;
+///     let samples = (0..4000).map(|_| {
+///         while !lis3dh.is_data_ready()? {}
+///         lis3dh.accel_norm()
+///     }).collect::<Result<Vec<_>, _>>()?;
+///
+///     export.csv(File::create("capture.csv")?, samples.iter().copied())?;
+///     export.wav(File::create("capture.wav")?, samples)?;
+///
+/// [`Accelerometer::accel_norm`]: accelerometer::Accelerometer::accel_norm
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Export {
+    pub datarate: DataRate,
+
+    /// Recorded in the JSON Lines metadata
+    pub mode: Mode,
+
+    /// Full scale of the WAV output
+    pub range: Range,
+
+    /// `WHO_AM_I` of the device, recorded in the JSON Lines metadata
+    pub chip_id: Option<u8>,
+
+    /// Time of the first sample, recorded in the JSON Lines metadata
+    pub start_time: Option<u64>,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self {
+            datarate: DataRate::Hz_400,
+            mode: Mode::HighResolution,
+            range: Range::default(),
+            chip_id: None,
+            start_time: None,
+        }
+    }
+}
+
+impl Export {
+    /// Describe the samples of the device with its current settings.
+    pub fn from_device<CORE>(
+        lis3dh: &mut Lis3dh<CORE>,
+    ) -> Result<Self, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+    {
+        let configuration = lis3dh.configuration();
+
+        Ok(Self {
+            datarate: configuration.datarate,
+            mode: configuration.mode,
+            range: lis3dh.get_range()?,
+            chip_id: Some(lis3dh.get_device_id()?),
+            start_time: None,
+        })
+    }
+
+    /// Describe the samples of a capture. The range is not part of the
+    /// header, as it may change between blocks.
+    pub fn from_capture(header: &CaptureHeader, range: Range) -> Self {
+        Self {
+            datarate: header.configuration.datarate,
+            mode: header.configuration.mode,
+            range,
+            chip_id: Some(header.chip_id),
+            start_time: Some(header.start_time),
+        }
+    }
+
+    fn sample_rate(&self) -> io::Result<f32> {
+        match self.datarate.sample_rate() {
+            rate if rate > 0.0 => Ok(rate),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot export samples without a data rate",
+            )),
+        }
+    }
+
+    /// Write a header row with units, followed by a row with the time in
+    /// seconds and the acceleration in g of each sample.
+    ///
+    ///     time (s),x (g),y (g),z (g)
+    ///     0,0.012,-0.004,1.001
+    pub fn csv<W, I>(&self, mut writer: W, samples: I) -> io::Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = F32x3>,
+    {
+        let rate = self.sample_rate()?;
+
+        writeln!(writer, "time (s),x (g),y (g),z (g)")?;
+        for (index, sample) in samples.into_iter().enumerate() {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                index as f64 / rate as f64,
+                sample.x,
+                sample.y,
+                sample.z
+            )?;
+        }
+
+        writer.flush()
+    }
+
+    /// Write a line with the metadata, followed by a line per sample with
+    /// the time in seconds and the acceleration in g. Values that are not
+    /// finite are written as `null`.
+    ///
+    ///     {"datarate_hz":400,"mode":"HighResolution","range_g":2,"units":"g","chip_id":51}
+    ///     {"t":0,"x":0.012,"y":-0.004,"z":1.001}
+    pub fn json_lines<W, I>(&self, mut writer: W, samples: I) -> io::Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = F32x3>,
+    {
+        let rate = self.sample_rate()?;
+
+        write!(
+            writer,
+            r#"{{"datarate_hz":{},"mode":"{:?}","range_g":{},"units":"g""#,
+            rate,
+            self.mode,
+            full_scale_g(self.range)
+        )?;
+        if let Some(chip_id) = self.chip_id {
+            write!(writer, r#","chip_id":{}"#, chip_id)?;
+        }
+        if let Some(start_time) = self.start_time {
+            write!(writer, r#","start_time":{}"#, start_time)?;
+        }
+        writeln!(writer, "}}")?;
+
+        for (index, sample) in samples.into_iter().enumerate() {
+            writeln!(
+                writer,
+                r#"{{"t":{},"x":{},"y":{},"z":{}}}"#,
+                index as f64 / rate as f64,
+                JsonNumber(sample.x),
+                JsonNumber(sample.y),
+                JsonNumber(sample.z)
+            )?;
+        }
+
+        writer.flush()
+    }
+
+    /// Write a 16-bit PCM WAV file with a channel per axis, at the sample
+    /// rate of the data rate. Full scale of the range maps to full scale of
+    /// the samples, e.g. ±2 g in [`Range::G2`].
+    pub fn wav<W, I>(&self, mut writer: W, samples: I) -> io::Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = F32x3>,
+    {
+        const CHANNELS: u16 = 3;
+        const BLOCK_ALIGN: u16 = CHANNELS * 2;
+
+        let rate = self.sample_rate()? as u32;
+        let scale = i16::MAX as f32 / full_scale_g(self.range) as f32;
+
+        let mut data = Vec::new();
+        for sample in samples {
+            for value in [sample.x, sample.y, sample.z] {
+                // `as` saturates and maps NaN to zero
+                let pcm = (value * scale).round() as i16;
+                data.extend_from_slice(&pcm.to_le_bytes());
+            }
+        }
+        let data_len = u32::try_from(data.len())
+            .ok()
+            .filter(|len| *len <= u32::MAX - 36)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "too many samples for WAV")
+            })?;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_len).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // PCM
+        writer.write_all(&CHANNELS.to_le_bytes())?;
+        writer.write_all(&rate.to_le_bytes())?;
+        writer.write_all(&(rate * BLOCK_ALIGN as u32).to_le_bytes())?;
+        writer.write_all(&BLOCK_ALIGN.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?; // bits per sample
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data_len.to_le_bytes())?;
+        writer.write_all(&data)?;
+
+        writer.flush()
+    }
+}
+
+const fn full_scale_g(range: Range) -> u8 {
+    match range {
+        Range::G16 => 16,
+        Range::G8 => 8,
+        Range::G4 => 4,
+        Range::G2 => 2,
+    }
+}
+
+/// Formats a number as JSON, which has no infinities or NaN.
+struct JsonNumber(f32);
+
+impl Display for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_finite() {
+            write!(f, "{}", self.0)
+        } else {
+            f.write_str("null")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export() -> Export {
+        Export {
+            datarate: DataRate::Hz_400,
+            ..Export::default()
+        }
+    }
+
+    fn samples() -> [F32x3; 2] {
+        [F32x3::new(0.5, -0.25, 1.0), F32x3::new(3.0, -3.0, f32::NAN)]
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = Vec::new();
+        export().csv(&mut out, samples()).unwrap();
+
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            "time (s),x (g),y (g),z (g)\n0,0.5,-0.25,1\n0.0025,3,-3,NaN\n"
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut out = Vec::new();
+        export().json_lines(&mut out, samples()).unwrap();
+
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            concat!(
+                r#"{"datarate_hz":400,"mode":"HighResolution","range_g":2,"units":"g"}"#,
+                "\n",
+                r#"{"t":0,"x":0.5,"y":-0.25,"z":1}"#,
+                "\n",
+                r#"{"t":0.0025,"x":3,"y":-3,"z":null}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn json_lines_metadata() {
+        let export = Export {
+            datarate: DataRate::Hz_10,
+            mode: Mode::LowPower,
+            range: Range::G16,
+            chip_id: Some(0x33),
+            start_time: Some(1_700_000_000_000),
+        };
+        let mut out = Vec::new();
+        export
+            .json_lines(
+                &mut out,
+                [F32x3::new(f32::INFINITY, f32::NEG_INFINITY, 0.0)],
+            )
+            .unwrap();
+
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            concat!(
+                r#"{"datarate_hz":10,"mode":"LowPower","range_g":16,"units":"g","#,
+                r#""chip_id":51,"start_time":1700000000000}"#,
+                "\n",
+                r#"{"t":0,"x":null,"y":null,"z":0}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn wav() {
+        let mut out = Vec::new();
+        export().wav(&mut out, samples()).unwrap();
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(b"RIFF");
+        expected.extend_from_slice(&(36u32 + 12).to_le_bytes());
+        expected.extend_from_slice(b"WAVEfmt ");
+        expected.extend_from_slice(&16u32.to_le_bytes());
+        expected.extend_from_slice(&1u16.to_le_bytes());
+        expected.extend_from_slice(&3u16.to_le_bytes());
+        expected.extend_from_slice(&400u32.to_le_bytes());
+        expected.extend_from_slice(&2400u32.to_le_bytes());
+        expected.extend_from_slice(&6u16.to_le_bytes());
+        expected.extend_from_slice(&16u16.to_le_bytes());
+        expected.extend_from_slice(b"data");
+        expected.extend_from_slice(&12u32.to_le_bytes());
+        // ±2 g full scale, out of range values saturate and NaN is silence
+        for pcm in [8192i16, -4096, 16384, i16::MAX, i16::MIN, 0] {
+            expected.extend_from_slice(&pcm.to_le_bytes());
+        }
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn requires_a_data_rate() {
+        let export = Export {
+            datarate: DataRate::PowerDown,
+            ..Export::default()
+        };
+
+        for result in [
+            export.csv(Vec::new(), samples()),
+            export.json_lines(Vec::new(), samples()),
+            export.wav(Vec::new(), samples()),
+        ] {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::interrupts::{Interrupt, InterruptPin, LatchInterruptRequest};
+use crate::{Error, Lis3dh, Lis3dhCore};
+
+/// Free-fall detection settings, see [`Lis3dh::configure_free_fall`].
+///
+/// In free fall the acceleration on all axes drops towards zero. The
+/// interrupt fires when all axes stay below `threshold_mg` for at least
+/// `duration_ms`.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FreeFallConfig {
+    /// Threshold for all axes in mili-g, default `350.0`
+    pub threshold_mg: f32,
+
+    /// Minimum duration in miliseconds, default `30.0`
+    pub duration_ms: f32,
+
+    /// Pin the interrupt is routed to, default [`InterruptPin::Int1`]
+    pub pin: InterruptPin,
+
+    /// Latch the interrupt until the source register is read, default
+    /// [`LatchInterruptRequest::Disable`]. Keep it disabled when using a
+    /// [`FreeFallDetector`], otherwise the end of the fall is only seen on
+    /// the next read.
+    pub latch: LatchInterruptRequest,
+}
+
+impl Default for FreeFallConfig {
+    fn default() -> Self {
+        Self {
+            threshold_mg: 350.0,
+            duration_ms: 30.0,
+            pin: InterruptPin::Int1,
+            latch: LatchInterruptRequest::Disable,
+        }
+    }
+}
+
+/// Free-fall transitions reported by a [`FreeFallDetector`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FreeFallEvent {
+    /// The device started falling
+    Started,
+
+    /// The device stopped falling after `duration_ms` miliseconds
+    Ended { duration_ms: u32 },
+}
+
+/// Tracks the free-fall interrupt and reports when a fall starts and ends.
+///
+/// Feed it the interrupt state, either the level of the interrupt pin or the
+/// source register via [`FreeFallDetector::poll`], together with a timestamp
+/// in miliseconds.
+///
+///     // let mut lis3dh = ...
+///     lis3dh.configure_free_fall(Interrupt1, FreeFallConfig::default())?;
+///
+///     let mut detector = FreeFallDetector::new();
+///     loop {
+///         match detector.poll(&mut lis3dh, Interrupt1, millis())? {
+///             Some(FreeFallEvent::Ended { duration_ms }) => { /* dropped for duration_ms */ }
+///             _ => {}
+///         }
+///     }
+///
+/// The reported duration starts once the interrupt fires, so it does not
+/// include the configured minimum duration.
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FreeFallDetector {
+    started_at: Option<u32>,
+}
+
+impl FreeFallDetector {
+    pub const fn new() -> Self {
+        Self { started_at: None }
+    }
+
+    /// Is the device currently falling?
+    pub const fn is_falling(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Update the detector with the current interrupt state at time `now_ms`.
+    /// The timestamp may wrap around.
+    pub fn update(&mut self, active: bool, now_ms: u32) -> Option<FreeFallEvent> {
+        match (self.started_at, active) {
+            (None, true) => {
+                self.started_at = Some(now_ms);
+                Some(FreeFallEvent::Started)
+            }
+            (Some(started_at), false) => {
+                self.started_at = None;
+                Some(FreeFallEvent::Ended {
+                    duration_ms: now_ms.wrapping_sub(started_at),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Read the interrupt source register of `int` and update the detector.
+    pub fn poll<CORE, I>(
+        &mut self,
+        lis3dh: &mut Lis3dh<CORE>,
+        int: I,
+        now_ms: u32,
+    ) -> Result<Option<FreeFallEvent>, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+        I: Interrupt,
+    {
+        let source = lis3dh.get_irq_src(int)?;
+
+        Ok(self.update(source.interrupt_active, now_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interrupts::{Interrupt1, Interrupt2};
+    use crate::mock::FakeDevice;
+    use crate::register::Register;
+    use crate::Configuration;
+
+    fn lis3dh() -> Lis3dh<FakeDevice> {
+        Lis3dh::new_with_core(FakeDevice::new(), Configuration::default()).unwrap()
+    }
+
+    fn register(lis3dh: &mut Lis3dh<FakeDevice>, register: Register) -> &mut u8 {
+        &mut lis3dh.core_mut().registers[register.addr() as usize]
+    }
+
+    #[test]
+    fn configure() {
+        let mut lis3dh = lis3dh();
+        lis3dh
+            .configure_free_fall(
+                Interrupt1,
+                FreeFallConfig {
+                    threshold_mg: 320.0,
+                    duration_ms: 50.0,
+                    ..FreeFallConfig::default()
+                },
+            )
+            .unwrap();
+
+        // AND combination of the low events
+        assert_eq!(*register(&mut lis3dh, Register::INT1_CFG), 0b1001_0101);
+        assert_eq!(*register(&mut lis3dh, Register::INT1_THS), 20);
+        assert_eq!(*register(&mut lis3dh, Register::INT1_DURATION), 20);
+        assert_eq!(*register(&mut lis3dh, Register::CTRL3), 1 << 6);
+    }
+
+    #[test]
+    fn configure_int2() {
+        let mut lis3dh = lis3dh();
+        lis3dh
+            .configure_free_fall(
+                Interrupt2,
+                FreeFallConfig {
+                    pin: InterruptPin::Int2,
+                    ..FreeFallConfig::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(*register(&mut lis3dh, Register::INT2_CFG), 0b1001_0101);
+        assert_eq!(*register(&mut lis3dh, Register::CTRL3), 0);
+        assert_eq!(*register(&mut lis3dh, Register::CTRL6), 1 << 5);
+    }
+
+    #[test]
+    fn rejects_long_durations() {
+        let mut lis3dh = lis3dh();
+        let result = lis3dh.configure_free_fall(
+            Interrupt1,
+            FreeFallConfig {
+                duration_ms: 1000.0,
+                ..FreeFallConfig::default()
+            },
+        );
+
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+        assert_eq!(*register(&mut lis3dh, Register::INT1_CFG), 0);
+    }
+
+    #[test]
+    fn update() {
+        let mut detector = FreeFallDetector::new();
+        assert_eq!(detector.update(false, 0), None);
+        assert!(!detector.is_falling());
+
+        assert_eq!(detector.update(true, 100), Some(FreeFallEvent::Started));
+        assert_eq!(detector.update(true, 150), None);
+        assert!(detector.is_falling());
+
+        assert_eq!(
+            detector.update(false, 250),
+            Some(FreeFallEvent::Ended { duration_ms: 150 })
+        );
+        assert_eq!(detector.update(false, 300), None);
+        assert!(!detector.is_falling());
+    }
+
+    #[test]
+    fn duration_wraps_around() {
+        let mut detector = FreeFallDetector::new();
+        assert_eq!(
+            detector.update(true, u32::MAX - 10),
+            Some(FreeFallEvent::Started)
+        );
+        assert_eq!(
+            detector.update(false, 5),
+            Some(FreeFallEvent::Ended { duration_ms: 16 })
+        );
+    }
+
+    #[test]
+    fn poll() {
+        let mut lis3dh = lis3dh();
+        let mut detector = FreeFallDetector::new();
+
+        *register(&mut lis3dh, Register::INT1_SRC) = 0b0101_0101;
+        assert_eq!(
+            detector.poll(&mut lis3dh, Interrupt1, 10).unwrap(),
+            Some(FreeFallEvent::Started)
+        );
+
+        assert_eq!(detector.poll(&mut lis3dh, Interrupt1, 20).unwrap(), None);
+
+        *register(&mut lis3dh, Register::INT1_SRC) = 0;
+        assert_eq!(
+            detector.poll(&mut lis3dh, Interrupt1, 40).unwrap(),
+            Some(FreeFallEvent::Ended { duration_ms: 30 })
+        );
+    }
+}
@@ -0,0 +1,457 @@
+use accelerometer::vector::I16x3;
+
+use crate::register::*;
+use crate::{Error, Lis3dh, Lis3dhCore};
+
+/// A problem found by the [`HealthMonitor`], or its recovery.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HealthEvent {
+    /// `WHO_AM_I` returned an unexpected value
+    WrongDeviceId(u8),
+
+    /// The control registers no longer match the applied configuration,
+    /// usually because the device was reset
+    ConfigurationLost,
+
+    /// The same sample was read a number of times in a row
+    StuckData,
+
+    /// No samples were observed between a number of checks in a row
+    MissingDataReady,
+
+    /// The device was initialized again
+    Reinitialized,
+
+    /// Initializing the device again failed
+    ReinitializationFailed,
+}
+
+/// Number of [`HealthEvent`]s of each kind since the monitor was created.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HealthCounters {
+    pub checks: u32,
+    pub wrong_device_id: u32,
+    pub configuration_lost: u32,
+    pub stuck_data: u32,
+    pub missing_data_ready: u32,
+    pub reinitialized: u32,
+    pub reinitialization_failed: u32,
+}
+
+/// Health monitor settings.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HealthConfig {
+    /// Number of identical samples in a row reported as stuck data, default
+    /// `50`. The noise of a working sensor makes long runs very unlikely.
+    pub stuck_samples: u16,
+
+    /// Number of checks in a row without observed samples reported as
+    /// missing data-ready, default `3`
+    pub missing_data_ready_checks: u8,
+
+    /// Initialize the device again when a problem is found, default `true`
+    pub reinitialize: bool,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            stuck_samples: 50,
+            missing_data_ready_checks: 3,
+            reinitialize: true,
+        }
+    }
+}
+
+/// Detects devices that were reset, stopped sampling or return constant data,
+/// and optionally initializes them again. The last `N` events are kept in a
+/// log.
+///
+/// Call [`HealthMonitor::check`] periodically, less often than the data rate,
+/// and pass every sample you read to [`HealthMonitor::observe`]. Data is
+/// reported missing when no sample was observed between checks.
+///
+///     // let mut lis3dh = ...
+///     let mut monitor = HealthMonitor::<8>::new(HealthConfig::default());
+///     loop {
+///         let sample = lis3dh.accel_raw()?;
+///         monitor.observe(sample);
+///
+///         if second_elapsed() && !monitor.check(&mut lis3dh)? {
+///             for event in monitor.events() {
+///                 log_event(event);
+///             }
+///         }
+///     }
+#[derive(Debug, Clone)]
+pub struct HealthMonitor<const N: usize> {
+    config: HealthConfig,
+    counters: HealthCounters,
+
+    log: [Option<HealthEvent>; N],
+    next: usize,
+
+    last_sample: Option<I16x3>,
+    /// Number of identical samples in a row
+    run: u16,
+    /// Samples observed since the last check
+    observed: u16,
+    without_data: u8,
+}
+
+impl<const N: usize> HealthMonitor<N> {
+    pub fn new(config: HealthConfig) -> Self {
+        Self {
+            config,
+            counters: HealthCounters::default(),
+            log: [None; N],
+            next: 0,
+            last_sample: None,
+            run: 0,
+            observed: 0,
+            without_data: 0,
+        }
+    }
+
+    pub fn counters(&self) -> HealthCounters {
+        self.counters
+    }
+
+    /// The logged events, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = HealthEvent> + '_ {
+        let (newer, older) = self.log.split_at(self.next);
+
+        older.iter().chain(newer.iter()).filter_map(|event| *event)
+    }
+
+    /// Feed a sample, as returned by
+    /// [`RawAccelerometer::accel_raw`](accelerometer::RawAccelerometer::accel_raw),
+    /// to detect stuck data. Returns `false` when the data is stuck.
+    pub fn observe(&mut self, sample: I16x3) -> bool {
+        let repeated = matches!(
+            self.last_sample,
+            Some(last) if (last.x, last.y, last.z) == (sample.x, sample.y, sample.z)
+        );
+        self.last_sample = Some(sample);
+        self.observed = self.observed.saturating_add(1);
+
+        self.run = if repeated {
+            self.run.saturating_add(1)
+        } else {
+            1
+        };
+
+        if self.run == self.config.stuck_samples {
+            self.record(HealthEvent::StuckData);
+        }
+
+        !self.is_stuck()
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.run >= self.config.stuck_samples
+    }
+
+    /// Verify `WHO_AM_I`, compare the control registers to the applied
+    /// configuration and check that samples were observed since the last
+    /// check. When a problem
+    /// is found and [`HealthConfig::reinitialize`] is set, the device is
+    /// initialized again with [`Lis3dh::reinitialize`].
+    ///
+    /// Returns `true` when no problem was found.
+    pub fn check<CORE>(
+        &mut self,
+        lis3dh: &mut Lis3dh<CORE>,
+    ) -> Result<bool, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+    {
+        self.counters.checks = self.counters.checks.wrapping_add(1);
+        let mut healthy = true;
+
+        let device_id = lis3dh.get_device_id()?;
+        if device_id != DEVICE_ID {
+            self.record(HealthEvent::WrongDeviceId(device_id));
+            healthy = false;
+        }
+
+        // TEMP_CFG_REG to CTRL_REG6
+        let mut regs = [0u8; 7];
+        lis3dh.read_registers(Register::TEMP_CFG, &mut regs)?;
+        let mut expected = regs;
+        let config = lis3dh.configuration();
+        config.update_registers(&mut expected);
+
+        // Only the bits owned by the configuration, BDU may also have been
+        // set by enabling the temperature sensor
+        let temp_cfg = ADC_EN | TEMP_EN;
+        let ctrl4 = HR | if config.block_data_update { BDU } else { 0 };
+        if regs[0] & temp_cfg != expected[0] & temp_cfg
+            || regs[1] != expected[1]
+            || regs[4] & ctrl4 != expected[4] & ctrl4
+        {
+            self.record(HealthEvent::ConfigurationLost);
+            healthy = false;
+        }
+
+        // Reading a sample clears `ZYXDA`, so the status register can not
+        // tell whether data keeps arriving
+        let observed = core::mem::replace(&mut self.observed, 0);
+        if config.datarate != DataRate::PowerDown && observed == 0 {
+            self.without_data = self.without_data.saturating_add(1);
+            if self.without_data == self.config.missing_data_ready_checks {
+                self.record(HealthEvent::MissingDataReady);
+            }
+            healthy &= self.without_data < self.config.missing_data_ready_checks;
+        } else {
+            self.without_data = 0;
+        }
+
+        healthy &= !self.is_stuck();
+
+        if !healthy && self.config.reinitialize {
+            if lis3dh.reinitialize().is_ok() {
+                self.record(HealthEvent::Reinitialized);
+            } else {
+                self.record(HealthEvent::ReinitializationFailed);
+            }
+
+            self.without_data = 0;
+            self.run = 0;
+        }
+
+        Ok(healthy)
+    }
+
+    fn record(&mut self, event: HealthEvent) {
+        let counter = match event {
+            HealthEvent::WrongDeviceId(_) => &mut self.counters.wrong_device_id,
+            HealthEvent::ConfigurationLost => &mut self.counters.configuration_lost,
+            HealthEvent::StuckData => &mut self.counters.stuck_data,
+            HealthEvent::MissingDataReady => &mut self.counters.missing_data_ready,
+            HealthEvent::Reinitialized => &mut self.counters.reinitialized,
+            HealthEvent::ReinitializationFailed => &mut self.counters.reinitialization_failed,
+        };
+        *counter = counter.wrapping_add(1);
+
+        if N > 0 {
+            self.log[self.next] = Some(event);
+            self.next = (self.next + 1) % N;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::Configuration;
+
+    fn lis3dh(config: Configuration) -> Lis3dh<FakeDevice> {
+        Lis3dh::new_with_core(FakeDevice::new(), config).unwrap()
+    }
+
+    fn register(lis3dh: &mut Lis3dh<FakeDevice>, register: Register) -> &mut u8 {
+        &mut lis3dh.core_mut().registers[register.addr() as usize]
+    }
+
+    fn monitor() -> HealthMonitor<4> {
+        HealthMonitor::new(HealthConfig::default())
+    }
+
+    fn events<const N: usize>(monitor: &HealthMonitor<N>) -> ([Option<HealthEvent>; 4], usize) {
+        let mut events = [None; 4];
+        let mut count = 0;
+        for (slot, event) in events.iter_mut().zip(monitor.events()) {
+            *slot = Some(event);
+            count += 1;
+        }
+
+        (events, count)
+    }
+
+    #[test]
+    fn healthy_device() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = monitor();
+
+        for check in 0..3 {
+            assert!(monitor.observe(I16x3::new(check, 0, 1000)));
+            assert!(monitor.check(&mut lis3dh).unwrap());
+        }
+
+        assert_eq!(events(&monitor).1, 0);
+        assert_eq!(monitor.counters().checks, 3);
+    }
+
+    #[test]
+    fn stuck_data() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = monitor();
+
+        for _ in 0..49 {
+            assert!(monitor.observe(I16x3::new(1, 2, 3)));
+        }
+        assert!(!monitor.observe(I16x3::new(1, 2, 3)));
+        assert!(!monitor.observe(I16x3::new(1, 2, 3)));
+        assert_eq!(monitor.counters().stuck_data, 1);
+
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(
+            events(&monitor).0[..2],
+            [
+                Some(HealthEvent::StuckData),
+                Some(HealthEvent::Reinitialized)
+            ]
+        );
+
+        // The run starts over after reinitializing
+        assert!(monitor.observe(I16x3::new(1, 2, 3)));
+        assert!(monitor.check(&mut lis3dh).unwrap());
+    }
+
+    #[test]
+    fn wrong_device_id() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = monitor();
+        *register(&mut lis3dh, Register::WHOAMI) = 0x32;
+
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(
+            events(&monitor).0[..2],
+            [
+                Some(HealthEvent::WrongDeviceId(0x32)),
+                Some(HealthEvent::ReinitializationFailed)
+            ]
+        );
+        assert_eq!(monitor.counters().wrong_device_id, 1);
+        assert_eq!(monitor.counters().reinitialization_failed, 1);
+    }
+
+    #[test]
+    fn reset_device() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = monitor();
+        let ctrl1 = *register(&mut lis3dh, Register::CTRL1);
+
+        // Power-on values
+        *register(&mut lis3dh, Register::TEMP_CFG) = 0;
+        *register(&mut lis3dh, Register::CTRL1) = 0x07;
+        *register(&mut lis3dh, Register::CTRL4) = 0;
+
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(
+            events(&monitor).0[..2],
+            [
+                Some(HealthEvent::ConfigurationLost),
+                Some(HealthEvent::Reinitialized)
+            ]
+        );
+        assert_eq!(*register(&mut lis3dh, Register::CTRL1), ctrl1);
+
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(monitor.check(&mut lis3dh).unwrap());
+    }
+
+    #[test]
+    fn ignores_bits_outside_the_configuration() {
+        let mut lis3dh = lis3dh(Configuration {
+            block_data_update: false,
+            ..Configuration::default()
+        });
+        let mut monitor = monitor();
+
+        // Sets BDU as well
+        lis3dh.enable_temp(true).unwrap();
+        lis3dh.enable_temp(false).unwrap();
+        lis3dh.set_range(Range::G16).unwrap();
+        *register(&mut lis3dh, Register::CTRL2) = 0x01;
+        *register(&mut lis3dh, Register::CTRL5) = 0x08;
+
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(monitor.check(&mut lis3dh).unwrap());
+
+        lis3dh.set_mode(Mode::Normal).unwrap();
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(monitor.check(&mut lis3dh).unwrap());
+
+        // Changed behind the driver's back
+        *register(&mut lis3dh, Register::CTRL4) |= HR;
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(monitor.counters().configuration_lost, 1);
+    }
+
+    #[test]
+    fn missing_data_ready() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = monitor();
+
+        assert!(monitor.check(&mut lis3dh).unwrap());
+        assert!(monitor.check(&mut lis3dh).unwrap());
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(
+            events(&monitor).0[..2],
+            [
+                Some(HealthEvent::MissingDataReady),
+                Some(HealthEvent::Reinitialized)
+            ]
+        );
+
+        // Any observed sample resets the count
+        assert!(monitor.check(&mut lis3dh).unwrap());
+        monitor.observe(I16x3::new(1, 2, 3));
+        assert!(monitor.check(&mut lis3dh).unwrap());
+        assert!(monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(monitor.counters().missing_data_ready, 1);
+    }
+
+    #[test]
+    fn powered_down_device_has_no_data() {
+        let mut lis3dh = lis3dh(Configuration {
+            datarate: DataRate::PowerDown,
+            ..Configuration::default()
+        });
+        let mut monitor = monitor();
+
+        for _ in 0..5 {
+            assert!(monitor.check(&mut lis3dh).unwrap());
+        }
+    }
+
+    #[test]
+    fn without_reinitialization() {
+        let mut lis3dh = lis3dh(Configuration::default());
+        let mut monitor = HealthMonitor::<2>::new(HealthConfig {
+            reinitialize: false,
+            missing_data_ready_checks: 1,
+            ..HealthConfig::default()
+        });
+        *register(&mut lis3dh, Register::CTRL1) = 0x07;
+
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert!(!monitor.check(&mut lis3dh).unwrap());
+        assert_eq!(*register(&mut lis3dh, Register::CTRL1), 0x07);
+        assert_eq!(monitor.counters().reinitialized, 0);
+
+        // The log keeps the last two events, oldest first
+        assert_eq!(monitor.counters().configuration_lost, 2);
+        assert_eq!(
+            events(&monitor),
+            (
+                [
+                    Some(HealthEvent::MissingDataReady),
+                    Some(HealthEvent::ConfigurationLost),
+                    None,
+                    None
+                ],
+                2
+            )
+        );
+    }
+}
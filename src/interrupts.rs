@@ -1,18 +1,22 @@
 use crate::register::*;
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Interrupt1;
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Interrupt2;
 
-pub trait Interrupt {
+pub trait Interrupt: Copy {
     fn cfg_reg() -> Register;
     fn ths_reg() -> Register;
     fn src_reg() -> Register;
     fn duration_reg() -> Register;
     fn lir_int_bit() -> u8;
     fn d4d_int_bit() -> u8;
+    /// Routing bit in `CTRL_REG3` and `CTRL_REG6`
+    fn ia_bit() -> u8;
 }
 
 impl Interrupt for Interrupt1 {
@@ -39,6 +43,10 @@ impl Interrupt for Interrupt1 {
     fn d4d_int_bit() -> u8 {
         2
     }
+
+    fn ia_bit() -> u8 {
+        1 << 6
+    }
 }
 
 impl Interrupt for Interrupt2 {
@@ -65,6 +73,10 @@ impl Interrupt for Interrupt2 {
     fn d4d_int_bit() -> u8 {
         0
     }
+
+    fn ia_bit() -> u8 {
+        1 << 5
+    }
 }
 
 /// When to generate an interrupt.
@@ -77,20 +89,16 @@ impl Interrupt for Interrupt2 {
 /// | 0 | 1 | 6-direction movement recognition  |
 /// | 1 | 0 | AND combination of interrupt events  |
 /// | 1 | 1 | 6-direction position recognition  |
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum InterruptMode {
+    #[default]
     OrCombination = 0b00 << 6,
     Movement = 0b01 << 6,
     AndCombination = 0b10 << 6,
     Position = 0b11 << 6,
 }
 
-impl Default for InterruptMode {
-    fn default() -> Self {
-        InterruptMode::OrCombination
-    }
-}
-
 impl InterruptMode {
     const fn from_bits(input: u8) -> Self {
         match input >> 6 {
@@ -121,7 +129,8 @@ impl From<u8> for InterruptMode {
 }
 
 /// Configure which events on which axes trigger an interrupt.
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[doc(alias = "INT1_CFG")]
 #[doc(alias = "INT2_CFG")]
 pub struct InterruptConfig {
@@ -217,7 +226,8 @@ impl InterruptConfig {
     }
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[doc(alias = "CTRL_REG3")]
 pub struct IrqPin1Config {
     pub click_en: bool,    // 7
@@ -229,7 +239,8 @@ pub struct IrqPin1Config {
     pub overrun_en: bool,  // 1
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[doc(alias = "CTRL_REG6")]
 pub struct IrqPin2Config {
     pub click_en: bool,   // 7
@@ -243,6 +254,7 @@ pub struct IrqPin2Config {
 pub trait IrqPin {
     fn ctrl_reg() -> Register;
     fn bits(self) -> u8;
+    fn from_bits(bits: u8) -> Self;
 }
 
 impl IrqPin for IrqPin1Config {
@@ -259,6 +271,18 @@ impl IrqPin for IrqPin1Config {
             | (self.wtm_en as u8) << 2
             | (self.overrun_en as u8) << 1
     }
+
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            click_en: bits & (1 << 7) != 0,
+            ia1_en: bits & (1 << 6) != 0,
+            ia2_en: bits & (1 << 5) != 0,
+            zyxda_en: bits & (1 << 4) != 0,
+            adc321da_en: bits & (1 << 3) != 0,
+            wtm_en: bits & (1 << 2) != 0,
+            overrun_en: bits & (1 << 1) != 0,
+        }
+    }
 }
 
 impl IrqPin for IrqPin2Config {
@@ -274,9 +298,50 @@ impl IrqPin for IrqPin2Config {
             | (self.act_en as u8) << 3
             | (self.active_low as u8) << 1
     }
+
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            click_en: bits & (1 << 7) != 0,
+            ia1_en: bits & (1 << 6) != 0,
+            ia2_en: bits & (1 << 5) != 0,
+            boot_en: bits & (1 << 4) != 0,
+            act_en: bits & (1 << 3) != 0,
+            active_low: bits & (1 << 1) != 0,
+        }
+    }
+}
+
+/// Interrupt mode, events, latch and 4D detection of an IRQ source, as
+/// returned by [`get_irq_src_and_control`].
+///
+/// [`get_irq_src_and_control`]: crate::Lis3dh::get_irq_src_and_control
+pub type IrqSrcAndControl = (
+    InterruptMode,
+    InterruptConfig,
+    LatchInterruptRequest,
+    Detect4D,
+);
+
+/// One of the two physical interrupt pins.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptPin {
+    #[default]
+    Int1,
+    Int2,
+}
+
+impl InterruptPin {
+    pub(crate) const fn ctrl_reg(self) -> Register {
+        match self {
+            InterruptPin::Int1 => Register::CTRL3,
+            InterruptPin::Int2 => Register::CTRL6,
+        }
+    }
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[doc(alias = "INT1_SRC")]
 #[doc(alias = "INT2_SRC")]
 pub struct InterruptSource {
@@ -307,21 +372,50 @@ impl InterruptSource {
     }
 }
 
+/// Click interrupt source. Decoded from the `CLICK_SRC` register.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[doc(alias = "CLICK_SRC")]
+pub struct ClickSource {
+    pub interrupt_active: bool,
+
+    pub double_click: bool,
+    pub single_click: bool,
+
+    /// The click was detected with a negative acceleration
+    pub negative: bool,
+
+    pub z_axis: bool,
+    pub y_axis: bool,
+    pub x_axis: bool,
+}
+
+impl ClickSource {
+    pub const fn from_bits(input: u8) -> Self {
+        // NOTE the leftmost bit is unused
+        Self {
+            interrupt_active: input & (1 << 6) != 0,
+            double_click: input & (1 << 5) != 0,
+            single_click: input & (1 << 4) != 0,
+            negative: input & (1 << 3) != 0,
+            z_axis: input & (1 << 2) != 0,
+            y_axis: input & (1 << 1) != 0,
+            x_axis: input & (1 << 0) != 0,
+        }
+    }
+}
+
 /// Latch (keep active) the interrupt until the [`get_irq_src`] is read.
 ///
 /// [`get_irq_src`]: crate::Lis3dh::get_irq_src
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum LatchInterruptRequest {
     Enable,
+    #[default]
     Disable,
 }
 
-impl Default for LatchInterruptRequest {
-    fn default() -> Self {
-        LatchInterruptRequest::Disable
-    }
-}
-
 impl From<bool> for LatchInterruptRequest {
     fn from(input: bool) -> Self {
         if input {
@@ -334,18 +428,14 @@ impl From<bool> for LatchInterruptRequest {
 
 /// 4D detection is a subset of the 6D detection where detection on the Z axis is disabled.
 /// This setting only has effect when the interrupt mode is either `Movement` or `Position`.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Detect4D {
     Enable,
+    #[default]
     Disable,
 }
 
-impl Default for Detect4D {
-    fn default() -> Self {
-        Detect4D::Disable
-    }
-}
-
 impl From<bool> for Detect4D {
     fn from(input: bool) -> Self {
         if input {
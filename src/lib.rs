@@ -10,34 +10,84 @@
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::convert::{TryFrom, TryInto};
-use core::fmt::Debug;
+use core::fmt::{self, Debug, Display};
 
 pub use accelerometer;
 use accelerometer::error::Error as AccelerometerError;
-use accelerometer::vector::{F32x3, I16x3};
+use accelerometer::vector::{F32x3, I16x3, I32x3};
 use accelerometer::{Accelerometer, RawAccelerometer};
 
+use embedded_hal::blocking::delay::DelayUs;
 use embedded_hal::blocking::i2c::{self, WriteRead};
 use embedded_hal::blocking::spi::{self, Transfer};
 
 use embedded_hal::digital::v2::OutputPin;
 
+mod activity;
+mod auto_range;
+mod calibration;
+mod capture;
+mod crc;
+mod data_ready;
+mod events;
+#[cfg(feature = "std")]
+mod export;
+mod free_fall;
+mod health;
 mod interrupts;
+#[cfg(test)]
+mod mock;
+mod mounting;
+mod orientation;
 mod register;
+mod replay;
+mod retry;
+mod sample;
+#[cfg(feature = "storage")]
+mod storage;
+mod trace;
+#[cfg(feature = "uom")]
+mod units;
+
+pub use activity::{ActivityConfig, ActivityState, ActivityTracker};
+pub use auto_range::{AutoRange, AutoRangeConfig, RangedSample};
+pub use calibration::{Calibration, SixPositionCalibration};
+pub use capture::{CaptureError, CaptureHeader, CaptureWriter};
+pub use data_ready::{DataReady, DataReadyError, NoPin};
+pub use events::{Event, EventIter, Events};
+pub use free_fall::{FreeFallConfig, FreeFallDetector, FreeFallEvent};
+pub use health::{HealthConfig, HealthCounters, HealthEvent, HealthMonitor};
+pub use mounting::{Mounting, SignedAxis};
+pub use orientation::{Orientation, OrientationDebouncer};
+pub use replay::{ByteSink, RecordBuffer, Recorder, Replay, ReplayError};
+pub use retry::{Retry, RetryCounters, RetryPolicy};
+pub use sample::{Sample, SampleFlags};
+pub use trace::{Access, TraceBuffer, TraceSink, Traced, Transaction, TRACE_DATA};
 
 use interrupts::*;
 pub use interrupts::{
-    Detect4D, Interrupt1, Interrupt2, InterruptConfig, InterruptMode, InterruptSource, IrqPin,
-    IrqPin1Config, IrqPin2Config, LatchInterruptRequest,
+    ClickSource, Detect4D, Interrupt1, Interrupt2, InterruptConfig, InterruptMode, InterruptPin,
+    InterruptSource, IrqPin, IrqPin1Config, IrqPin2Config, IrqSrcAndControl, LatchInterruptRequest,
 };
 
+#[cfg(feature = "std")]
+pub use capture::CaptureReader;
+#[cfg(feature = "std")]
+pub use export::Export;
 use register::*;
-pub use register::{DataRate, DataStatus, Duration, Mode, Range, SlaveAddr, Threshold};
+#[cfg(feature = "storage")]
+pub use storage::{MemoryFlash, Settings, StorageError};
+
+pub use register::{DataRate, DataStatus, Duration, Mode, Range, Register, SlaveAddr, Threshold};
 
 /// Accelerometer errors, generic around another error type `E` representing
 /// an (optional) cause of this error.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<BusError, PinError> {
     /// I²C bus error
     Bus(BusError),
@@ -57,11 +107,131 @@ pub enum Error<BusError, PinError> {
 
     /// Invalid address provided
     WrongAddress,
+
+    /// Conflicting or out-of-range configuration
+    InvalidConfiguration(ConfigError),
+}
+
+/// Configuration conflicts, reported by [`Configuration::validate`] and the
+/// interrupt configuration methods.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigError {
+    /// Temperature readings depend on block data update, which cannot be
+    /// combined with [`Mode::LowPower`]
+    TemperatureInLowPower,
+
+    /// 4D detection requires [`InterruptMode::Movement`] or
+    /// [`InterruptMode::Position`]
+    Detect4DWithoutDirection,
+
+    /// The threshold does not fit in the 7 bits of the threshold registers
+    ThresholdOutOfRange,
+
+    /// The duration does not fit in the duration register
+    DurationOutOfRange,
+
+    /// [`AutoRangeConfig`] with `min` above `max` or overlapping step
+    /// thresholds
+    InvalidAutoRange,
+
+    /// The bytes passed to [`Configuration::from_bytes`] or
+    /// [`Calibration::from_bytes`] do not encode a configuration or
+    /// calibration
+    InvalidEncoding,
+
+    /// A [`Mounting`] that does not use every sensor axis exactly once, or a
+    /// matrix that is not orthonormal
+    InvalidMounting,
+
+    /// The interrupt configuration cannot be mapped through a
+    /// [`Mounting::Matrix`]
+    MountingNotAxisAligned,
+}
+
+impl<BusError, PinError> Display for Error<BusError, PinError>
+where
+    BusError: Debug,
+    PinError: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Bus(error) => write!(f, "bus error: {:?}", error),
+            Error::Pin(error) => write!(f, "pin error: {:?}", error),
+            Error::InvalidDataRate => f.write_str("invalid data rate"),
+            Error::InvalidMode => f.write_str("invalid operating mode"),
+            Error::InvalidRange => f.write_str("invalid full-scale range"),
+            Error::WriteToReadOnly => f.write_str("attempted to write to a read-only register"),
+            Error::WrongAddress => f.write_str("unexpected device id, wrong address?"),
+            Error::InvalidConfiguration(error) => write!(f, "invalid configuration: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<BusError, PinError> std::error::Error for Error<BusError, PinError>
+where
+    BusError: Debug,
+    PinError: Debug,
+{
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigError::TemperatureInLowPower => {
+                "temperature readings are not supported in low-power mode"
+            }
+            ConfigError::Detect4DWithoutDirection => {
+                "4D detection requires movement or position interrupt mode"
+            }
+            ConfigError::ThresholdOutOfRange => "threshold does not fit in 7 bits",
+            ConfigError::DurationOutOfRange => "duration does not fit in the duration register",
+            ConfigError::InvalidAutoRange => "invalid auto-range limits or thresholds",
+            ConfigError::InvalidEncoding => "invalid configuration or calibration encoding",
+            ConfigError::InvalidMounting => {
+                "mounting does not use every axis exactly once or is not orthonormal"
+            }
+            ConfigError::MountingNotAxisAligned => {
+                "interrupt configuration cannot be mapped through the mounting matrix"
+            }
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+impl<BusError, PinError> From<ConfigError> for Error<BusError, PinError> {
+    fn from(error: ConfigError) -> Self {
+        Error::InvalidConfiguration(error)
+    }
 }
 
+/// Standard acceleration of gravity in m/s²
+const STANDARD_GRAVITY: f32 = 9.806_65;
+
 /// `LIS3DH` driver.
 pub struct Lis3dh<CORE> {
     core: CORE,
+
+    /// Temperature corresponding to a temperature sensor output of zero
+    temp_offset: f32,
+
+    /// Number of upcoming samples taken before the output settled
+    unsettled: u8,
+
+    /// Transformation from the sensor frame to the board frame
+    mounting: Mounting,
+
+    /// Correction applied to readings in g
+    calibration: Calibration,
+
+    /// Sequence number of the last new [`Sample`], zero before the first
+    sequence: u32,
+
+    /// The applied configuration, kept up to date by the setters
+    config: Configuration,
 }
 
 impl<I2C, E> Lis3dh<Lis3dhI2C<I2C>>
@@ -103,16 +273,7 @@ where
         address: SlaveAddr,
         config: Configuration,
     ) -> Result<Self, Error<E, core::convert::Infallible>> {
-        let core = Lis3dhI2C {
-            i2c,
-            address: address.addr(),
-        };
-
-        let mut lis3dh = Lis3dh { core };
-
-        lis3dh.initialize(config)?;
-
-        Ok(lis3dh)
+        Self::new_with_core(Lis3dhI2C::new(i2c, address), config)
     }
 }
 
@@ -161,13 +322,7 @@ where
         nss: NSS,
         config: Configuration,
     ) -> Result<Self, Error<ESPI, ENSS>> {
-        let core = Lis3dhSPI { spi, nss };
-
-        let mut lis3dh = Lis3dh { core };
-
-        lis3dh.initialize(config)?;
-
-        Ok(lis3dh)
+        Self::new_with_core(Lis3dhSPI::new(spi, nss), config)
     }
 }
 
@@ -175,61 +330,163 @@ impl<CORE> Lis3dh<CORE>
 where
     CORE: Lis3dhCore,
 {
+    /// Create a new LIS3DH driver on top of a transport, e.g. one wrapped in
+    /// [`Retry`]:
+    ///
+    ///     let core = Retry::new(
+    ///         Lis3dhI2C::new(i2c, SlaveAddr::Default),
+    ///         delay,
+    ///         RetryPolicy::default(),
+    ///     );
+    ///     let lis3dh = Lis3dh::new_with_core(core, Configuration::default())?;
+    pub fn new_with_core(
+        core: CORE,
+        config: Configuration,
+    ) -> Result<Self, Error<CORE::BusError, CORE::PinError>> {
+        let mut lis3dh = Self::with_core(core);
+
+        lis3dh.initialize(config)?;
+
+        Ok(lis3dh)
+    }
+
+    /// The underlying transport.
+    pub fn core(&self) -> &CORE {
+        &self.core
+    }
+
+    /// Mutable access to the underlying transport.
+    pub fn core_mut(&mut self) -> &mut CORE {
+        &mut self.core
+    }
+
+    fn with_core(core: CORE) -> Self {
+        Self {
+            core,
+            temp_offset: 25.0,
+            unsettled: 0,
+            mounting: Mounting::default(),
+            calibration: Calibration::default(),
+            sequence: 0,
+            config: Configuration::default(),
+        }
+    }
+
     /// Initalize the device given the configuration
     fn initialize(
         &mut self,
         conf: Configuration,
     ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        conf.validate()?;
+
         if self.get_device_id()? != DEVICE_ID {
             return Err(Error::WrongAddress);
         }
 
-        if conf.block_data_update || conf.enable_temperature {
-            // Block data update
-            self.write_register(Register::CTRL4, BDU)?;
-        }
+        self.apply_configuration(conf)
+    }
+
+    /// Apply a new [`Configuration`] to an already initialized device.
+    ///
+    /// The device is powered down while the control registers are rewritten,
+    /// and the output data rate is set last, so no samples are produced with
+    /// a partially applied configuration. Settings not covered by
+    /// [`Configuration`] (range, interrupts, FIFO, ...) are kept.
+    pub fn reconfigure(
+        &mut self,
+        conf: Configuration,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        conf.validate()?;
 
-        self.set_mode(conf.mode)?;
+        self.apply_configuration(conf)
+    }
+
+    /// Power down, write the final `TEMP_CFG_REG` to `CTRL_REG6` values in
+    /// one burst, then set the data rate.
+    fn apply_configuration(
+        &mut self,
+        conf: Configuration,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        let mut regs = [0u8; 7];
+        self.read_registers(Register::TEMP_CFG, &mut regs)?;
 
-        self.set_datarate(conf.datarate)?;
+        // The burst starts at TEMP_CFG_REG, before CTRL_REG1
+        self.write_register(Register::CTRL1, regs[1] & !ODR_MASK)?;
 
-        self.enable_axis((conf.enable_x_axis, conf.enable_y_axis, conf.enable_z_axis))?;
+        conf.update_registers(&mut regs);
 
-        if conf.enable_temperature {
-            self.enable_temp(true)?;
-        }
+        let ctrl1 = regs[1];
+        regs[1] &= !ODR_MASK;
+
+        self.write_registers(Register::TEMP_CFG, &regs)?;
+        self.write_register(Register::CTRL1, ctrl1)?;
+
+        self.unsettled = conf.mode.settling_samples();
+        self.config = conf;
 
-        // Enable ADCs.
-        self.write_register(Register::TEMP_CFG, ADC_EN)
+        Ok(())
     }
 
-    /// `WHO_AM_I` register.
-    pub fn get_device_id(&mut self) -> Result<u8, Error<CORE::BusError, CORE::PinError>> {
-        self.read_register(Register::WHOAMI)
+    /// The applied configuration, including changes made with
+    /// [`Lis3dh::set_mode`], [`Lis3dh::set_datarate`] and
+    /// [`Lis3dh::enable_temp`].
+    pub fn configuration(&self) -> Configuration {
+        self.config
     }
 
-    /// X,Y,Z-axis enable.
-    /// `CTRL_REG1`: `Xen`, `Yen`, `Zen`
-    fn enable_axis(
+    /// Check the device id and apply the configuration again, e.g. after the
+    /// device was reset. Interrupt and FIFO settings are not restored.
+    pub fn reinitialize(&mut self) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        self.initialize(self.config)
+    }
+
+    /// Set how the sensor is mounted on the board. From then on samples are
+    /// returned in the board frame, and the interrupt configuration and
+    /// source methods, [`Lis3dh::get_orientation`] and
+    /// [`Lis3dh::poll_events`] use board axes.
+    ///
+    /// With a [`Mounting::Matrix`] that does more than permute and negate
+    /// axes, these methods return [`ConfigError::MountingNotAxisAligned`]
+    /// unless the interrupt treats all axes alike. Returns
+    /// [`ConfigError::InvalidMounting`] for an invalid mounting, see
+    /// [`Mounting::validate`].
+    pub fn set_mounting(
         &mut self,
-        (x, y, z): (bool, bool, bool),
+        mounting: Mounting,
     ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
-        self.modify_register(Register::CTRL1, |mut ctrl1| {
-            ctrl1 &= !(X_EN | Y_EN | Z_EN); // disable all axes
+        mounting.validate()?;
+        self.mounting = mounting;
 
-            ctrl1 |= if x { X_EN } else { 0 };
-            ctrl1 |= if y { Y_EN } else { 0 };
-            ctrl1 |= if z { Z_EN } else { 0 };
+        Ok(())
+    }
 
-            ctrl1
-        })
+    /// The current mounting, see [`Lis3dh::set_mounting`].
+    pub fn mounting(&self) -> Mounting {
+        self.mounting
+    }
+
+    /// Set the offset and gain correction applied by
+    /// [`Accelerometer::accel_norm`] and [`Lis3dh::accel_si`]. The correction
+    /// is applied in the board frame, see [`Lis3dh::set_mounting`].
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    /// The current calibration, see [`Lis3dh::set_calibration`].
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// `WHO_AM_I` register.
+    pub fn get_device_id(&mut self) -> Result<u8, Error<CORE::BusError, CORE::PinError>> {
+        self.read_register(Register::WHOAMI)
     }
 
     /// Operating mode selection.
     /// `CTRL_REG1`: `LPen` bit, `CTRL_REG4`: `HR` bit.
-    /// You need to wait for stabilization after setting. In future this
-    /// function will be deprecated and instead take a `Delay` to do this for
-    /// you.
+    /// The output needs to stabilize after setting, the following samples
+    /// are marked as unsettled (see [`Lis3dh::is_settled`]). Use
+    /// [`Lis3dh::set_mode_blocking`] to wait for stabilization instead.
     ///
     /// | From           | To             | Wait for   |
     /// |:---------------|:---------------|:-----------|
@@ -239,7 +496,16 @@ where
     /// | Normal         | HighResolution | 7/datarate |
     /// | LowPower       | Normal         | 1/datarate |
     /// | LowPower       | HighResolution | 7/datarate |
+    ///
+    /// Returns [`ConfigError::TemperatureInLowPower`] when switching to
+    /// [`Mode::LowPower`] with the temperature sensor enabled.
     pub fn set_mode(&mut self, mode: Mode) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        Configuration {
+            mode,
+            ..self.config
+        }
+        .validate()?;
+
         match mode {
             Mode::LowPower => {
                 self.register_set_bits(Register::CTRL1, LP_EN)?;
@@ -255,9 +521,26 @@ where
             }
         }
 
+        self.mark_unsettled(mode.settling_samples());
+        self.config.mode = mode;
+
         Ok(())
     }
 
+    /// Operating mode selection, waiting for the output to stabilize.
+    /// See [`Lis3dh::set_mode`].
+    pub fn set_mode_blocking<D>(
+        &mut self,
+        mode: Mode,
+        delay: &mut D,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
+    where
+        D: DelayUs<u32>,
+    {
+        self.set_mode(mode)?;
+        self.wait_settled(delay)
+    }
+
     /// Read the current operating mode.
     pub fn get_mode(&mut self) -> Result<Mode, Error<CORE::BusError, CORE::PinError>> {
         let ctrl1 = self.read_register(Register::CTRL1)?;
@@ -277,6 +560,7 @@ where
     }
 
     /// Data rate selection.
+    /// The following samples are marked as unsettled, see [`Lis3dh::set_mode`].
     pub fn set_datarate(
         &mut self,
         datarate: DataRate,
@@ -288,7 +572,25 @@ where
             ctrl1 |= datarate.bits() << 4;
 
             ctrl1
-        })
+        })?;
+
+        self.mark_unsettled(self.config.mode.settling_samples());
+        self.config.datarate = datarate;
+
+        Ok(())
+    }
+
+    /// Data rate selection, waiting for the output to stabilize.
+    pub fn set_datarate_blocking<D>(
+        &mut self,
+        datarate: DataRate,
+        delay: &mut D,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
+    where
+        D: DelayUs<u32>,
+    {
+        self.set_datarate(datarate)?;
+        self.wait_settled(delay)
     }
 
     /// Read the current data selection rate.
@@ -300,6 +602,7 @@ where
     }
 
     /// Full-scale selection.
+    /// The next sample is marked as unsettled, see [`Lis3dh::set_mode`].
     pub fn set_range(&mut self, range: Range) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
         self.modify_register(Register::CTRL4, |mut ctrl4| {
             // Mask off lowest 4 bits
@@ -308,7 +611,59 @@ where
             ctrl4 |= range.bits() << 4;
 
             ctrl4
-        })
+        })?;
+
+        self.mark_unsettled(1);
+
+        Ok(())
+    }
+
+    /// Full-scale selection, waiting for the output to stabilize.
+    pub fn set_range_blocking<D>(
+        &mut self,
+        range: Range,
+        delay: &mut D,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
+    where
+        D: DelayUs<u32>,
+    {
+        self.set_range(range)?;
+        self.wait_settled(delay)
+    }
+
+    /// Have the outputs stabilized after the last mode, data rate or range
+    /// change? Samples read while this returns `false` should be discarded.
+    pub fn is_settled(&self) -> bool {
+        self.unsettled == 0
+    }
+
+    /// Number of upcoming new samples before the outputs have stabilized.
+    /// Only reads of new data, with `ZYXDA` set, count towards it.
+    pub fn unsettled_samples(&self) -> u8 {
+        self.unsettled
+    }
+
+    fn mark_unsettled(&mut self, samples: u8) {
+        self.unsettled = self.unsettled.max(samples);
+    }
+
+    /// Wait for the remaining unsettled samples to pass at the current data
+    /// rate.
+    fn wait_settled<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
+    where
+        D: DelayUs<u32>,
+    {
+        let datarate = self.config.datarate;
+
+        if datarate != DataRate::PowerDown {
+            delay.delay_us(self.unsettled as u32 * datarate.period_us());
+            self.unsettled = 0;
+        }
+
+        Ok(())
     }
 
     /// Read the current full-scale.
@@ -351,53 +706,269 @@ where
     }
 
     /// Temperature sensor enable.
-    /// `TEMP_CGF_REG`: `TEMP_EN`, the BDU bit in `CTRL_REG4` is also set.
+    /// `TEMP_CFG_REG`: `ADC_EN` and `TEMP_EN`, the BDU bit in `CTRL_REG4` is
+    /// also set. Disabling the temperature sensor leaves the ADCs enabled.
+    /// Returns [`ConfigError::TemperatureInLowPower`] when enabling it in
+    /// [`Mode::LowPower`].
     pub fn enable_temp(
         &mut self,
         enable: bool,
     ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
-        self.register_xset_bits(Register::TEMP_CFG, ADC_EN & TEMP_EN, enable)?;
+        Configuration {
+            enable_temperature: enable,
+            ..self.config
+        }
+        .validate()?;
 
-        // enable block data update (required for temp reading)
         if enable {
-            self.register_xset_bits(Register::CTRL4, BDU, true)?;
+            self.register_set_bits(Register::TEMP_CFG, ADC_EN | TEMP_EN)?;
+
+            // enable block data update (required for temp reading)
+            self.register_set_bits(Register::CTRL4, BDU)?;
+        } else {
+            self.register_clear_bits(Register::TEMP_CFG, TEMP_EN)?;
         }
 
+        self.config.enable_temperature = enable;
+
         Ok(())
     }
 
-    /// Raw temperature sensor data as `i16`. The temperature sensor __must__
-    /// be enabled via `enable_temp` prior to reading.
-    pub fn get_temp_out(&mut self) -> Result<i16, Error<CORE::BusError, CORE::PinError>> {
-        let out_l = self.read_register(Register::OUT_ADC3_L)?;
-        let out_h = self.read_register(Register::OUT_ADC3_H)?;
+    /// Auxiliary ADC channels 1 to 3, read in a single burst. The values are
+    /// right-justified to the resolution of the current mode: 8 bits in
+    /// [`Mode::LowPower`], 10 bits otherwise. Channel 3 is connected to the
+    /// temperature sensor when it is enabled.
+    #[doc(alias = "OUT_ADC1_L")]
+    pub fn get_adc_out(&mut self) -> Result<[i16; 3], Error<CORE::BusError, CORE::PinError>> {
+        let shift = match self.get_mode()? {
+            Mode::LowPower => 8,
+            Mode::Normal | Mode::HighResolution => 6,
+        };
+
+        let [adc1, adc2, adc3] = self.read_adc_raw()?;
+
+        Ok([adc1 >> shift, adc2 >> shift, adc3 >> shift])
+    }
 
-        Ok(i16::from_le_bytes([out_l, out_h]))
+    /// Raw temperature sensor data as `i16`, right-justified like
+    /// [`Lis3dh::get_adc_out`]. The temperature sensor __must__ be enabled
+    /// via `enable_temp` prior to reading.
+    pub fn get_temp_out(&mut self) -> Result<i16, Error<CORE::BusError, CORE::PinError>> {
+        Ok(self.get_adc_out()?[2])
     }
 
     /// Temperature sensor data converted to `f32`. Output is in degree
     /// celsius. The temperature sensor __must__ be enabled via `enable_temp`
     /// prior to reading.
+    ///
+    /// The sensor only measures temperature changes (1 digit/°C): an output
+    /// of zero corresponds to a part-dependent reference temperature. This
+    /// reference is assumed to be 25°C unless calibrated with
+    /// [`Lis3dh::set_temp_offset`] or [`Lis3dh::calibrate_temp`].
     pub fn get_temp_outf(&mut self) -> Result<f32, Error<CORE::BusError, CORE::PinError>> {
-        let temp_out = self.get_temp_out()?;
+        let [_, _, temp_out] = self.read_adc_raw()?;
 
-        Ok(temp_out as f32 / 256.0 + 25.0)
+        Ok(temp_out as f32 / 256.0 + self.temp_offset)
     }
 
-    /// Modify a register's value. Read the current value of the register,
-    /// update the value with the provided function, and set the register to
-    /// the return value.
-    fn modify_register<F>(
+    /// Set the temperature in degree celsius corresponding to a temperature
+    /// sensor output of zero, see [`Lis3dh::get_temp_outf`].
+    pub fn set_temp_offset(&mut self, offset: f32) {
+        self.temp_offset = offset;
+    }
+
+    /// Calibrate the temperature offset against a known current temperature
+    /// in degree celsius, see [`Lis3dh::get_temp_outf`].
+    pub fn calibrate_temp(
         &mut self,
-        register: Register,
-        f: F,
-    ) -> Result<(), Error<CORE::BusError, CORE::PinError>>
-    where
-        F: FnOnce(u8) -> u8,
-    {
-        let value = self.read_register(register)?;
+        temperature: f32,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        let [_, _, temp_out] = self.read_adc_raw()?;
+        self.temp_offset = temperature - temp_out as f32 / 256.0;
 
-        self.write_register(register, f(value))
+        Ok(())
+    }
+
+    /// All three ADC channels, left-justified.
+    fn read_adc_raw(&mut self) -> Result<[i16; 3], Error<CORE::BusError, CORE::PinError>> {
+        let adc_bytes = self.read_adc_bytes()?;
+
+        Ok([
+            i16::from_le_bytes([adc_bytes[0], adc_bytes[1]]),
+            i16::from_le_bytes([adc_bytes[2], adc_bytes[3]]),
+            i16::from_le_bytes([adc_bytes[4], adc_bytes[5]]),
+        ])
+    }
+
+    /// Get the acceleration right-justified for the resolution of the current
+    /// operating mode, e.g. in the range -2048..=2047 in
+    /// [`Mode::HighResolution`].
+    pub fn accel_counts(&mut self) -> Result<I16x3, Error<CORE::BusError, CORE::PinError>> {
+        let mode = self.get_mode()?;
+
+        self.read_counts(mode)
+    }
+
+    /// Get the acceleration in milli-g, using integer math only.
+    pub fn accel_mg(&mut self) -> Result<I32x3, Error<CORE::BusError, CORE::PinError>> {
+        let (mode, range) = self.get_mode_and_range()?;
+        let scale = mode.sensitivity_mg(range) as i32;
+
+        let acc = self.read_counts(mode)?;
+
+        Ok(I32x3::new(
+            acc.x as i32 * scale,
+            acc.y as i32 * scale,
+            acc.z as i32 * scale,
+        ))
+    }
+
+    /// Get the acceleration in m/s², see [`Accelerometer::accel_norm`].
+    pub fn accel_si(&mut self) -> Result<F32x3, Error<CORE::BusError, CORE::PinError>> {
+        let acc = self.accel_uncalibrated()?;
+        let acc = self.calibration.apply(acc);
+
+        Ok(F32x3::new(
+            acc.x * STANDARD_GRAVITY,
+            acc.y * STANDARD_GRAVITY,
+            acc.z * STANDARD_GRAVITY,
+        ))
+    }
+
+    /// Get the acceleration in g using the datasheet sensitivity, without
+    /// applying the [`Calibration`].
+    pub fn accel_uncalibrated(&mut self) -> Result<F32x3, Error<CORE::BusError, CORE::PinError>> {
+        let (mode, range) = self.get_mode_and_range()?;
+
+        // Scale values are divided by 1000 in order to convert the resulting
+        // values from mG to G.
+        let scale = mode.sensitivity_mg(range) as f32 / 1000.0;
+
+        let acc = self.read_counts(mode)?;
+        let x = acc.x as f32 * scale;
+        let y = acc.y as f32 * scale;
+        let z = acc.z as f32 * scale;
+
+        Ok(F32x3::new(x, y, z))
+    }
+
+    /// Read a sample in milli-g together with a sequence number and flags
+    /// telling whether it can be trusted. See [`Lis3dh::accel_mg`].
+    #[doc(alias = "STATUS_REG")]
+    pub fn read_sample(&mut self) -> Result<Sample, Error<CORE::BusError, CORE::PinError>> {
+        // CTRL_REG1 to CTRL_REG4
+        let mut ctrl = [0u8; 4];
+        self.read_registers(Register::CTRL1, &mut ctrl)?;
+        let (mode, range) = Self::decode_mode_and_range(ctrl[0], ctrl[3])?;
+
+        // STATUS_REG followed by the output registers
+        let mut data = [0u8; 7];
+        self.read_registers(Register::STATUS, &mut data)?;
+
+        // Only new samples advance the settling and sequence counters
+        let stale = data[0] & ZYXDA == 0;
+        let settling = !self.is_settled();
+        if !stale {
+            self.unsettled = self.unsettled.saturating_sub(1);
+            self.sequence = self.sequence.wrapping_add(1);
+        }
+
+        let raw = I16x3::new(
+            i16::from_le_bytes([data[1], data[2]]),
+            i16::from_le_bytes([data[3], data[4]]),
+            i16::from_le_bytes([data[5], data[6]]),
+        );
+
+        let shift = mode.shift();
+        let (min, max) = (i16::MIN >> shift, i16::MAX >> shift);
+        let saturated = [raw.x, raw.y, raw.z]
+            .iter()
+            .any(|value| value >> shift == min || value >> shift == max);
+
+        let disabled = self.mounting.axes_to_board([
+            ctrl[0] & X_EN == 0,
+            ctrl[0] & Y_EN == 0,
+            ctrl[0] & Z_EN == 0,
+        ]);
+
+        let acc = self.mounting.sample_to_board(raw);
+        let scale = mode.sensitivity_mg(range) as i32;
+
+        let sample = Sample {
+            accel_mg: I32x3::new(
+                (acc.x >> shift) as i32 * scale,
+                (acc.y >> shift) as i32 * scale,
+                (acc.z >> shift) as i32 * scale,
+            ),
+            sequence: self.sequence,
+            flags: SampleFlags {
+                overrun: data[0] & ZYXOR != 0,
+                saturated,
+                settling,
+                stale,
+                disabled: (disabled[0], disabled[1], disabled[2]),
+            },
+        };
+
+        Ok(sample)
+    }
+
+    /// Read the operating mode and full-scale in one transaction.
+    fn get_mode_and_range(
+        &mut self,
+    ) -> Result<(Mode, Range), Error<CORE::BusError, CORE::PinError>> {
+        // CTRL_REG1 to CTRL_REG4
+        let mut ctrl = [0u8; 4];
+        self.read_registers(Register::CTRL1, &mut ctrl)?;
+
+        Self::decode_mode_and_range(ctrl[0], ctrl[3])
+    }
+
+    fn decode_mode_and_range(
+        ctrl1: u8,
+        ctrl4: u8,
+    ) -> Result<(Mode, Range), Error<CORE::BusError, CORE::PinError>> {
+        let mode = match (ctrl1 & LP_EN != 0, ctrl4 & HR != 0) {
+            (true, false) => Mode::LowPower,
+            (false, false) => Mode::Normal,
+            (false, true) => Mode::HighResolution,
+            _ => return Err(Error::InvalidMode),
+        };
+        let range = Range::try_from((ctrl4 & FS_MASK) >> 4).map_err(|_| Error::InvalidRange)?;
+
+        Ok((mode, range))
+    }
+
+    /// Read a sample and right-justify it for the given mode. The raw data is
+    /// in left-justified two's complement.
+    fn read_counts(&mut self, mode: Mode) -> Result<I16x3, Error<CORE::BusError, CORE::PinError>> {
+        let acc = self.read_accel_raw()?;
+        let shift = mode.shift();
+
+        Ok(I16x3::new(acc.x >> shift, acc.y >> shift, acc.z >> shift))
+    }
+
+    /// Raw acceleration data, see [`RawAccelerometer::accel_raw`].
+    fn read_accel_raw(&mut self) -> Result<I16x3, Error<CORE::BusError, CORE::PinError>> {
+        let accel_bytes = if self.is_settled() {
+            self.read_accel_bytes()?
+        } else {
+            // STATUS_REG followed by the output registers, only new samples
+            // count towards settling
+            let mut data = [0u8; 7];
+            self.read_registers(Register::STATUS, &mut data)?;
+            if data[0] & ZYXDA != 0 {
+                self.unsettled -= 1;
+            }
+
+            data[1..].try_into().unwrap()
+        };
+
+        let x = i16::from_le_bytes(accel_bytes[0..2].try_into().unwrap());
+        let y = i16::from_le_bytes(accel_bytes[2..4].try_into().unwrap());
+        let z = i16::from_le_bytes(accel_bytes[4..6].try_into().unwrap());
+
+        Ok(self.mounting.sample_to_board(I16x3::new(x, y, z)))
     }
 
     /// Clear the given bits in the given register.
@@ -418,21 +989,6 @@ where
         self.modify_register(reg, |v| v | bits)
     }
 
-    /// Set or clear the given given bits in the given register, depending on
-    /// the value of `set`.
-    fn register_xset_bits(
-        &mut self,
-        reg: Register,
-        bits: u8,
-        set: bool,
-    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
-        if set {
-            self.register_set_bits(reg, bits)
-        } else {
-            self.register_clear_bits(reg, bits)
-        }
-    }
-
     /// Configure one of the interrupt pins
     ///
     ///     lis3dh.configure_interrupt_pin(IrqPin1Config {
@@ -448,6 +1004,15 @@ where
         self.write_register(P::ctrl_reg(), pin.bits())
     }
 
+    /// Read back the configuration of one of the interrupt pins
+    ///
+    ///     let pin1: IrqPin1Config = lis3dh.get_interrupt_pin()?;
+    pub fn get_interrupt_pin<P: IrqPin>(
+        &mut self,
+    ) -> Result<P, Error<CORE::BusError, CORE::PinError>> {
+        Ok(P::from_bits(self.read_register(P::ctrl_reg())?))
+    }
+
     /// Configure an IRQ source
     ///
     /// Example: configure interrupt 1 to fire when there is movement along any of the axes.
@@ -475,6 +1040,8 @@ where
     /// Configure an IRQ source.
     ///
     /// LIS (latch interrupt request) will latch (keep active) the interrupt until the [`Lis3dh::get_irq_src`] is read.
+    /// Both the latch and 4D bits of this interrupt are always written, so disabling them takes effect
+    /// when reconfiguring.
     ///
     /// 4D detection is a subset of the 6D detection where detection on the Z axis is disabled.
     /// This setting only has effect when the interrupt mode is either `Movement` or `Position`.
@@ -488,6 +1055,9 @@ where
     ///         lis3dh::LatchInterruptRequest::Enable,
     ///         lis3dh::Detect4D::Enable,
     ///     )?;
+    ///
+    /// Enabling 4D detection with any other interrupt mode returns
+    /// [`ConfigError::Detect4DWithoutDirection`].
     pub fn configure_irq_src_and_control<I: Interrupt>(
         &mut self,
         _int: I,
@@ -501,20 +1071,62 @@ where
 
         let detect_4d = matches!(detect_4d, Detect4D::Enable);
 
-        if latch_interrupt_request || detect_4d {
-            let latch = (latch_interrupt_request as u8) << I::lir_int_bit();
-            let d4d = (detect_4d as u8) << I::d4d_int_bit();
-            self.register_set_bits(Register::CTRL5, latch | d4d)?;
+        if detect_4d
+            && !matches!(
+                interrupt_mode,
+                InterruptMode::Movement | InterruptMode::Position
+            )
+        {
+            return Err(ConfigError::Detect4DWithoutDirection.into());
         }
-        self.write_register(I::cfg_reg(), interrupt_config.to_bits(interrupt_mode))
+
+        let latch = 1 << I::lir_int_bit();
+        let d4d = 1 << I::d4d_int_bit();
+        self.modify_register(Register::CTRL5, |mut ctrl5| {
+            ctrl5 &= !(latch | d4d);
+
+            ctrl5 |= if latch_interrupt_request { latch } else { 0 };
+            ctrl5 |= if detect_4d { d4d } else { 0 };
+
+            ctrl5
+        })?;
+
+        let cfg = self
+            .mounting
+            .cfg_to_sensor(interrupt_config.to_bits(interrupt_mode))?;
+
+        self.write_register(I::cfg_reg(), cfg)
+    }
+
+    /// Read back the configuration of an IRQ source, as set by
+    /// [`Lis3dh::configure_irq_src_and_control`].
+    pub fn get_irq_src_and_control<I: Interrupt>(
+        &mut self,
+        _int: I,
+    ) -> Result<IrqSrcAndControl, Error<CORE::BusError, CORE::PinError>> {
+        let cfg = self.read_register(I::cfg_reg())?;
+        let cfg = self.mounting.src_to_board(cfg, cfg)?;
+        let ctrl5 = self.read_register(Register::CTRL5)?;
+
+        let latch = ctrl5 & (1 << I::lir_int_bit()) != 0;
+        let d4d = ctrl5 & (1 << I::d4d_int_bit()) != 0;
+
+        Ok((
+            InterruptMode::from(cfg),
+            InterruptConfig::from_bits(cfg),
+            latch.into(),
+            d4d.into(),
+        ))
     }
 
     /// Set the minimum duration for the Interrupt event to be recognized.
+    /// The duration registers are 7 bits wide, longer durations return
+    /// [`ConfigError::DurationOutOfRange`].
     ///
     /// Example: the event has to last at least 25 miliseconds to be recognized.
     ///
     ///     // let mut lis3dh = ...
-    ///     let duration = Duration::miliseconds(DataRate::Hz_400, 25.0);
+    ///     let duration = Duration::miliseconds(DataRate::Hz_400, 25.0)?;
     ///     lis3dh.configure_irq_duration(duration);
     #[doc(alias = "INT1_DURATION")]
     #[doc(alias = "INT2_DURATION")]
@@ -523,6 +1135,10 @@ where
         _int: I,
         duration: Duration,
     ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        if duration.0 > 0b0111_1111 {
+            return Err(ConfigError::DurationOutOfRange.into());
+        }
+
         self.write_register(I::duration_reg(), duration.0)
     }
 
@@ -531,7 +1147,7 @@ where
     /// Example: the event has have a magnitude of at least 1.1g to be recognized.
     ///
     ///     // let mut lis3dh = ...
-    ///     let threshold = Threshold::g(Range::G2, 1.1);
+    ///     let threshold = Threshold::g(Range::G2, 1.1)?;
     ///     lis3dh.configure_irq_threshold(threshold);
     #[doc(alias = "INT1_THS")]
     #[doc(alias = "INT2_THS")]
@@ -543,21 +1159,195 @@ where
         self.write_register(I::ths_reg(), threshold.0)
     }
 
-    /// Get interrupt source. The `interrupt_active` field is true when an interrupt is active.
-    /// The other fields specify what measurement caused the interrupt.
-    pub fn get_irq_src<I: Interrupt>(
+    /// Read back the minimum duration of an interrupt event, see
+    /// [`Lis3dh::configure_irq_duration`].
+    #[doc(alias = "INT1_DURATION")]
+    #[doc(alias = "INT2_DURATION")]
+    pub fn get_irq_duration<I: Interrupt>(
         &mut self,
         _int: I,
-    ) -> Result<InterruptSource, Error<CORE::BusError, CORE::PinError>> {
-        let irq_src = self.read_register(I::src_reg())?;
+    ) -> Result<Duration, Error<CORE::BusError, CORE::PinError>> {
+        Ok(Duration(self.read_register(I::duration_reg())?))
+    }
+
+    /// Read back the minimum magnitude of an interrupt event, see
+    /// [`Lis3dh::configure_irq_threshold`].
+    #[doc(alias = "INT1_THS")]
+    #[doc(alias = "INT2_THS")]
+    pub fn get_irq_threshold<I: Interrupt>(
+        &mut self,
+        _int: I,
+    ) -> Result<Threshold, Error<CORE::BusError, CORE::PinError>> {
+        Ok(Threshold(self.read_register(I::ths_reg())?))
+    }
+
+    /// Get interrupt source. The `interrupt_active` field is true when an interrupt is active.
+    /// The other fields specify what measurement caused the interrupt.
+    pub fn get_irq_src<I: Interrupt>(
+        &mut self,
+        _int: I,
+    ) -> Result<InterruptSource, Error<CORE::BusError, CORE::PinError>> {
+        let cfg = if self.mounting == Mounting::default() {
+            0
+        } else {
+            self.read_register(I::cfg_reg())?
+        };
+
+        let irq_src = self.read_register(I::src_reg())?;
+        let irq_src = self.mounting.src_to_board(cfg, irq_src)?;
+
         Ok(InterruptSource::from_bits(irq_src))
     }
 
+    /// Configure an interrupt generator for free-fall detection and route it
+    /// to a pin. The threshold and duration are converted using the current
+    /// range and data rate, so set those first.
+    ///
+    /// Example: fire interrupt 1 on the `INT1` pin when all axes stay below
+    /// 350mg for 30ms.
+    ///
+    ///     // let mut lis3dh = ...
+    ///     lis3dh.configure_free_fall(Interrupt1, FreeFallConfig::default())?;
+    ///
+    /// See [`FreeFallDetector`] to track the start and end of a fall.
+    pub fn configure_free_fall<I: Interrupt>(
+        &mut self,
+        int: I,
+        config: FreeFallConfig,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        let threshold = Threshold::mg(self.get_range()?, config.threshold_mg)?;
+        let duration = Duration::miliseconds(self.get_datarate()?, config.duration_ms)?;
+
+        self.configure_irq_threshold(int, threshold)?;
+        self.configure_irq_duration(int, duration)?;
+        self.configure_irq_src_and_control(
+            int,
+            InterruptMode::AndCombination,
+            InterruptConfig::low(),
+            config.latch,
+            Detect4D::Disable,
+        )?;
+
+        self.register_set_bits(config.pin.ctrl_reg(), I::ia_bit())
+    }
+
+    /// Read all interrupt source registers and return the pending events, so
+    /// a single interrupt handler can deal with every interrupt source.
+    ///
+    /// Reading the source registers clears latched interrupts.
+    ///
+    ///     // let mut lis3dh = ...
+    ///     for event in lis3dh.poll_events()? {
+    ///         match event {
+    ///             Event::DataReady => { /* read the sample */ }
+    ///             Event::FreeFall => { /* brace for impact */ }
+    ///             Event::Position(orientation) => { /* rotate the screen */ }
+    ///             _ => {}
+    ///         }
+    ///     }
+    #[doc(alias = "INT1_SRC")]
+    #[doc(alias = "INT2_SRC")]
+    #[doc(alias = "CLICK_SRC")]
+    #[doc(alias = "FIFO_SRC")]
+    pub fn poll_events(&mut self) -> Result<Events, Error<CORE::BusError, CORE::PinError>> {
+        let status = self.read_register(Register::STATUS)?;
+
+        // FIFO_SRC up to CLICK_SRC, skipping over the output registers so no
+        // sample is consumed
+        let mut src = [0u8; 11];
+        self.read_registers(Register::FIFO_SRC, &mut src)?;
+
+        let mounting = self.mounting;
+        let (int1_cfg, int1_src) = (src[1], src[2]);
+        let (int2_cfg, int2_src) = (src[5], src[6]);
+
+        Ok(Events {
+            status,
+            fifo_src: src[0],
+            int1_cfg: mounting.src_to_board(int1_cfg, int1_cfg)?,
+            int1_src: mounting.src_to_board(int1_cfg, int1_src)?,
+            int2_cfg: mounting.src_to_board(int2_cfg, int2_cfg)?,
+            int2_src: mounting.src_to_board(int2_cfg, int2_src)?,
+            click_src: mounting.click_to_board(src[10])?,
+        })
+    }
+
+    /// Get the orientation recognised by an interrupt generator configured in
+    /// [`InterruptMode::Position`] mode. With [`Detect4D::Enable`] only the
+    /// portrait and landscape orientations are recognised.
+    ///
+    /// Returns `None` when the interrupt generator is in another mode or no
+    /// orientation is recognised. See [`Orientation`] for the axis mapping.
+    pub fn get_orientation<I: Interrupt>(
+        &mut self,
+        _int: I,
+    ) -> Result<Option<Orientation>, Error<CORE::BusError, CORE::PinError>> {
+        let cfg = self.read_register(I::cfg_reg())?;
+        if !matches!(InterruptMode::from(cfg), InterruptMode::Position) {
+            return Ok(None);
+        }
+
+        let src = self.read_register(I::src_reg())?;
+        let source = InterruptSource::from_bits(self.mounting.src_to_board(cfg, src)?);
+
+        Ok(Orientation::from_source(source))
+    }
+
+    /// Configure 'Sleep to wake' and 'Return to sleep' and signal the
+    /// activity state on the `INT2` pin. The pin is asserted while the device
+    /// sleeps, see [`ActivityTracker`] to follow the transitions.
+    ///
+    /// The threshold and duration are converted using the current range and
+    /// data rate, so set those first. The polarity of both interrupt pins is
+    /// only changed when [`ActivityConfig::active_low`] is set.
+    ///
+    ///     // let mut lis3dh = ...
+    ///     lis3dh.set_datarate(DataRate::Hz_100)?;
+    ///     lis3dh.configure_activity(ActivityConfig {
+    ///         threshold_mg: 250.0,
+    ///         return_to_sleep_s: 5.0,
+    ///         ..ActivityConfig::default()
+    ///     })?;
+    #[doc(alias = "ACT_THS")]
+    #[doc(alias = "ACT_DUR")]
+    #[doc(alias = "I2_ACT")]
+    pub fn configure_activity(
+        &mut self,
+        config: ActivityConfig,
+    ) -> Result<(), Error<CORE::BusError, CORE::PinError>> {
+        let threshold = Threshold::mg(self.get_range()?, config.threshold_mg)?;
+
+        // ACT_DUR counts in steps of 8 / ODR, starting at 1 / ODR
+        let samples = self.get_datarate()?.sample_rate() * config.return_to_sleep_s;
+        let duration = (samples - 1.0) / 8.0;
+        if !(0.0..256.0).contains(&duration) {
+            return Err(ConfigError::DurationOutOfRange.into());
+        }
+
+        self.write_register(Register::ACT_THS, threshold.0)?;
+        self.write_register(Register::ACT_DUR, duration as u8)?;
+
+        self.modify_register(Register::CTRL6, |mut ctrl6| {
+            ctrl6 |= I2_ACT;
+
+            match config.active_low {
+                Some(true) => ctrl6 |= H_LACTIVE,
+                Some(false) => ctrl6 &= !H_LACTIVE,
+                None => {}
+            }
+
+            ctrl6
+        })
+    }
+
     /// Configure 'Sleep to wake' and 'Return to sleep' threshold and duration.
     ///
     /// The LIS3DH can be programmed to automatically switch to low-power mode upon recognition of a determined event.  
     /// Once the event condition is over, the device returns back to the preset normal or highresolution mode.
     ///
+    /// The duration is written as is, in steps of `8 / ODR`. Use
+    /// [`Lis3dh::configure_activity`] to configure it in seconds.
+    ///
     /// Example: enter low-power mode. When a measurement above 1.1g is registered, then wake up
     /// for 25ms to send the data.
     ///
@@ -566,8 +1356,8 @@ where
     ///     let range = Range::default();
     ///     let data_rate = DataRate::Hz_400;
     ///     
-    ///     let threshold = Threshold::g(range, 1.1);
-    ///     let duration = Duration::miliseconds(data_rate, 25.0);
+    ///     let threshold = Threshold::g(range, 1.1)?;
+    ///     let duration = Duration::miliseconds(data_rate, 25.0)?;
     ///     
     ///     lis3dh.configure_switch_to_low_power(threshold, duration)?;
     ///     
@@ -595,52 +1385,13 @@ where
 
     /// Get normalized ±g reading from the accelerometer. You should be reading
     /// based on data ready interrupt or if reading in a tight loop you should
-    /// waiting for `is_data_ready`.
+    /// waiting for `is_data_ready`. [`DataReady`] does either for you.
     fn accel_norm(&mut self) -> Result<F32x3, AccelerometerError<Self::Error>> {
         // The official driver from ST was used as a reference.
         // https://github.com/STMicroelectronics/STMems_Standard_C_drivers/tree/master/lis3dh_STdC
-        let mode = self.get_mode()?;
-        let range = self.get_range()?;
-
-        // See "2.1 Mechanical characteristics" in the datasheet to find the
-        // values below. Scale values have all been divided by 1000 in order
-        // to convert the resulting values from mG to G, while avoiding doing
-        // any actual division on the hardware.
-        let scale = match (mode, range) {
-            // High Resolution mode
-            (Mode::HighResolution, Range::G2) => 0.001,
-            (Mode::HighResolution, Range::G4) => 0.002,
-            (Mode::HighResolution, Range::G8) => 0.004,
-            (Mode::HighResolution, Range::G16) => 0.012,
-            // Normal mode
-            (Mode::Normal, Range::G2) => 0.004,
-            (Mode::Normal, Range::G4) => 0.008,
-            (Mode::Normal, Range::G8) => 0.016,
-            (Mode::Normal, Range::G16) => 0.048,
-            // Low Power mode
-            (Mode::LowPower, Range::G2) => 0.016,
-            (Mode::LowPower, Range::G4) => 0.032,
-            (Mode::LowPower, Range::G8) => 0.064,
-            (Mode::LowPower, Range::G16) => 0.192,
-        };
+        let acc = self.accel_uncalibrated()?;
 
-        // Depending on which Mode we are operating in, the data has different
-        // resolution. Using this knowledge, we determine how many bits the
-        // data needs to be shifted. This is necessary because the raw data
-        // is in left-justified two's complement and we would like for it to be
-        // right-justified instead.
-        let shift: u8 = match mode {
-            Mode::HighResolution => 4, // High Resolution:  12-bit
-            Mode::Normal => 6,         // Normal:           10-bit
-            Mode::LowPower => 8,       // Low Power:         8-bit
-        };
-
-        let acc_raw = self.accel_raw()?;
-        let x = (acc_raw.x >> shift) as f32 * scale;
-        let y = (acc_raw.y >> shift) as f32 * scale;
-        let z = (acc_raw.z >> shift) as f32 * scale;
-
-        Ok(F32x3::new(x, y, z))
+        Ok(self.calibration.apply(acc))
     }
 
     /// Get the sample rate of the accelerometer data.
@@ -659,15 +1410,9 @@ where
 
     /// Get raw acceleration data from the accelerometer. You should be reading
     /// based on data ready interrupt or if reading in a tight loop you should
-    /// waiting for `is_data_ready`.
+    /// waiting for `is_data_ready`. [`DataReady`] does either for you.
     fn accel_raw(&mut self) -> Result<I16x3, AccelerometerError<Self::Error>> {
-        let accel_bytes = self.read_accel_bytes()?;
-
-        let x = i16::from_le_bytes(accel_bytes[0..2].try_into().unwrap());
-        let y = i16::from_le_bytes(accel_bytes[2..4].try_into().unwrap());
-        let z = i16::from_le_bytes(accel_bytes[4..6].try_into().unwrap());
-
-        Ok(I16x3::new(x, y, z))
+        Ok(self.read_accel_raw()?)
     }
 }
 
@@ -686,7 +1431,54 @@ pub trait Lis3dhCore {
         register: Register,
     ) -> Result<u8, Error<Self::BusError, Self::PinError>>;
 
-    fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>>;
+    /// Read consecutive registers starting at `start` in a single
+    /// transaction, using the auto-increment of the register address.
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>>;
+
+    /// Write consecutive registers starting at `start` in a single
+    /// transaction, using the auto-increment of the register address.
+    /// Returns [`Error::WriteToReadOnly`] without writing anything if any of
+    /// the registers is read-only or reserved.
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>>;
+
+    /// Modify a register's value. Read the current value of the register,
+    /// update the value with the provided function, and set the register to
+    /// the return value. Transports that retry transactions retry the read
+    /// and the write together, so `f` may be called more than once.
+    fn modify_register<F>(
+        &mut self,
+        register: Register,
+        f: F,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>>
+    where
+        F: Fn(u8) -> u8,
+    {
+        let value = self.read_register(register)?;
+
+        self.write_register(register, f(value))
+    }
+
+    /// Read from the registers for each of the 3 axes.
+    fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
+        let mut data = [0u8; 6];
+        self.read_registers(Register::OUT_X_L, &mut data)?;
+        Ok(data)
+    }
+
+    /// Read from the registers for each of the 3 ADC channels.
+    fn read_adc_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
+        let mut data = [0u8; 6];
+        self.read_registers(Register::OUT_ADC1_L, &mut data)?;
+        Ok(data)
+    }
 }
 
 impl<CORE> Lis3dhCore for Lis3dh<CORE>
@@ -711,9 +1503,40 @@ where
         self.core.read_register(register)
     }
 
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.core.read_registers(start, buf)
+    }
+
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.core.write_registers(start, data)
+    }
+
+    fn modify_register<F>(
+        &mut self,
+        register: Register,
+        f: F,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>>
+    where
+        F: Fn(u8) -> u8,
+    {
+        self.core.modify_register(register, f)
+    }
+
     fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
         self.core.read_accel_bytes()
     }
+
+    fn read_adc_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
+        self.core.read_adc_bytes()
+    }
 }
 
 /// Marker to indicate I2C is used to communicate with the Lis3dh
@@ -725,6 +1548,16 @@ pub struct Lis3dhI2C<I2C> {
     address: u8,
 }
 
+impl<I2C> Lis3dhI2C<I2C> {
+    /// Create a transport, see [`Lis3dh::new_with_core`].
+    pub fn new(i2c: I2C, address: SlaveAddr) -> Self {
+        Self {
+            i2c,
+            address: address.addr(),
+        }
+    }
+}
+
 impl<I2C, E> Lis3dhCore for Lis3dhI2C<I2C>
 where
     I2C: WriteRead<Error = E> + i2c::Write<Error = E>,
@@ -732,16 +1565,6 @@ where
     type BusError = E;
     type PinError = core::convert::Infallible;
 
-    /// Read from the registers for each of the 3 axes.
-    fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
-        let mut data = [0u8; 6];
-
-        self.i2c
-            .write_read(self.address, &[Register::OUT_X_L.addr() | 0x80], &mut data)
-            .map_err(Error::Bus)
-            .and(Ok(data))
-    }
-
     /// Write a byte to the given register.
     fn write_register(
         &mut self,
@@ -769,6 +1592,36 @@ where
             .map_err(Error::Bus)
             .and(Ok(data[0]))
     }
+
+    /// Read many registers, the MSB of the sub-address enables auto-increment.
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.i2c
+            .write_read(self.address, &[start.addr() | 0x80], buf)
+            .map_err(Error::Bus)
+    }
+
+    /// Write many registers, the MSB of the sub-address enables auto-increment.
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        if !Register::writable_range(start, data.len()) {
+            return Err(Error::WriteToReadOnly);
+        }
+
+        let mut buf = [0u8; 1 + REGISTER_COUNT];
+        buf[0] = start.addr() | 0x80;
+        buf[1..=data.len()].copy_from_slice(data);
+
+        self.i2c
+            .write(self.address, &buf[..=data.len()])
+            .map_err(Error::Bus)
+    }
 }
 
 /// Marker to indicate SPI is used to communicate with the Lis3dh
@@ -779,6 +1632,13 @@ pub struct Lis3dhSPI<SPI, NSS> {
     nss: NSS,
 }
 
+impl<SPI, NSS> Lis3dhSPI<SPI, NSS> {
+    /// Create a transport, see [`Lis3dh::new_with_core`].
+    pub fn new(spi: SPI, nss: NSS) -> Self {
+        Self { spi, nss }
+    }
+}
+
 impl<SPI, NSS, ESPI, ENSS> Lis3dhSPI<SPI, NSS>
 where
     SPI: spi::Write<u8, Error = ESPI> + Transfer<u8, Error = ESPI>,
@@ -793,37 +1653,6 @@ where
     fn nss_turn_off(&mut self) -> Result<(), Error<ESPI, ENSS>> {
         self.nss.set_high().map_err(Error::Pin)
     }
-
-    /// Writes to many registers. Does not check whether all registers
-    /// can be written to
-    unsafe fn write_multiple_regs(
-        &mut self,
-        start_register: Register,
-        data: &[u8],
-    ) -> Result<(), Error<ESPI, ENSS>> {
-        self.nss_turn_on()?;
-        let res = self
-            .spi
-            .write(&[start_register.addr() | 0x40])
-            .and_then(|_| self.spi.write(data))
-            .map_err(Error::Bus);
-        self.nss_turn_off()?;
-        res
-    }
-
-    /// Read from the registers for each of the 3 axes.
-    fn read_multiple_regs(
-        &mut self,
-        start_register: Register,
-        buf: &mut [u8],
-    ) -> Result<(), Error<ESPI, ENSS>> {
-        self.nss_turn_on()?;
-        self.spi
-            .write(&[start_register.addr() | 0xC0])
-            .and_then(|_| self.spi.transfer(buf))
-            .map_err(Error::Bus)?;
-        self.nss_turn_off()
-    }
 }
 
 impl<SPI, NSS, ESPI, ENSS> Lis3dhCore for Lis3dhSPI<SPI, NSS>
@@ -834,19 +1663,9 @@ where
     type BusError = ESPI;
     type PinError = ENSS;
 
-    /// Read from the registers for each of the 3 axes.
-    fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<ESPI, ENSS>> {
-        let mut data = [0u8; 6];
-        self.read_multiple_regs(Register::OUT_X_L, &mut data)?;
-        Ok(data)
-    }
-
     /// Write a byte to the given register.
     fn write_register(&mut self, register: Register, value: u8) -> Result<(), Error<ESPI, ENSS>> {
-        if register.read_only() {
-            return Err(Error::WriteToReadOnly);
-        }
-        unsafe { self.write_multiple_regs(register, &[value]) }
+        self.write_registers(register, &[value])
     }
 
     /// Read a byte from the given register.
@@ -854,16 +1673,47 @@ where
         let mut data = [0];
 
         self.nss_turn_on()?;
-        self.spi
+        let res = self
+            .spi
             .write(&[register.addr() | 0x80])
-            .and_then(|_| self.spi.transfer(&mut data))
-            .map_err(Error::Bus)?;
+            .and_then(|_| self.spi.transfer(&mut data).map(|_| ()))
+            .map_err(Error::Bus);
+        self.nss_turn_off()?;
+        res.and(Ok(data[0]))
+    }
+
+    /// Read many registers, the MS bit enables auto-increment.
+    fn read_registers(&mut self, start: Register, buf: &mut [u8]) -> Result<(), Error<ESPI, ENSS>> {
+        self.nss_turn_on()?;
+        let res = self
+            .spi
+            .write(&[start.addr() | 0xC0])
+            .and_then(|_| self.spi.transfer(buf).map(|_| ()))
+            .map_err(Error::Bus);
         self.nss_turn_off()?;
-        Ok(data[0])
+        res
+    }
+
+    /// Write many registers, the MS bit enables auto-increment.
+    fn write_registers(&mut self, start: Register, data: &[u8]) -> Result<(), Error<ESPI, ENSS>> {
+        if !Register::writable_range(start, data.len()) {
+            return Err(Error::WriteToReadOnly);
+        }
+
+        self.nss_turn_on()?;
+        let res = self
+            .spi
+            .write(&[start.addr() | 0x40])
+            .and_then(|_| self.spi.write(data))
+            .map_err(Error::Bus);
+        self.nss_turn_off()?;
+        res
     }
 }
 
 /// Sensor configuration options
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Configuration {
     /// The operating mode, default [`Mode::HighResolution`].
     pub mode: Mode,
@@ -901,3 +1751,333 @@ impl Default for Configuration {
         }
     }
 }
+
+impl Configuration {
+    /// Check the configuration for conflicting options. This is also done
+    /// by the constructors before anything is written to the device.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.enable_temperature && self.mode == Mode::LowPower {
+            return Err(ConfigError::TemperatureInLowPower);
+        }
+
+        Ok(())
+    }
+
+    /// Size of the serialized configuration
+    pub const BYTES: usize = 3;
+
+    /// Serialize as the operating mode, the data rate and a byte of flags.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mode = match self.mode {
+            Mode::HighResolution => 0,
+            Mode::Normal => 1,
+            Mode::LowPower => 2,
+        };
+
+        let flags = (self.enable_x_axis as u8)
+            | (self.enable_y_axis as u8) << 1
+            | (self.enable_z_axis as u8) << 2
+            | (self.block_data_update as u8) << 3
+            | (self.enable_temperature as u8) << 4;
+
+        [mode, self.datarate.bits(), flags]
+    }
+
+    /// Deserialize a configuration written by [`Configuration::to_bytes`].
+    /// Returns [`ConfigError::InvalidEncoding`] for unknown values, and
+    /// validates the configuration.
+    pub fn from_bytes(bytes: &[u8; Self::BYTES]) -> Result<Self, ConfigError> {
+        let [mode, datarate, flags] = *bytes;
+
+        let mode = match mode {
+            0 => Mode::HighResolution,
+            1 => Mode::Normal,
+            2 => Mode::LowPower,
+            _ => return Err(ConfigError::InvalidEncoding),
+        };
+        let datarate = DataRate::try_from(datarate).map_err(|_| ConfigError::InvalidEncoding)?;
+        if flags & !0b1_1111 != 0 {
+            return Err(ConfigError::InvalidEncoding);
+        }
+
+        let conf = Self {
+            mode,
+            datarate,
+            enable_x_axis: flags & 1 != 0,
+            enable_y_axis: flags & (1 << 1) != 0,
+            enable_z_axis: flags & (1 << 2) != 0,
+            block_data_update: flags & (1 << 3) != 0,
+            enable_temperature: flags & (1 << 4) != 0,
+        };
+        conf.validate()?;
+
+        Ok(conf)
+    }
+
+    /// Update the `TEMP_CFG_REG` to `CTRL_REG6` values with the settings in
+    /// this configuration, leaving all other bits untouched.
+    fn update_registers(&self, regs: &mut [u8; 7]) {
+        let [temp_cfg, ctrl1, _ctrl2, _ctrl3, ctrl4, ctrl5, _ctrl6] = regs;
+
+        // The ADCs are always enabled
+        *temp_cfg = ADC_EN | if self.enable_temperature { TEMP_EN } else { 0 };
+
+        *ctrl1 = self.datarate.bits() << 4;
+        *ctrl1 |= if self.mode == Mode::LowPower {
+            LP_EN
+        } else {
+            0
+        };
+        *ctrl1 |= if self.enable_x_axis { X_EN } else { 0 };
+        *ctrl1 |= if self.enable_y_axis { Y_EN } else { 0 };
+        *ctrl1 |= if self.enable_z_axis { Z_EN } else { 0 };
+
+        *ctrl4 &= !(BDU | HR);
+        *ctrl4 |= if self.block_data_update || self.enable_temperature {
+            BDU
+        } else {
+            0
+        };
+        *ctrl4 |= if self.mode == Mode::HighResolution {
+            HR
+        } else {
+            0
+        };
+
+        // Never reboot the memory content by writing back a set BOOT bit
+        *ctrl5 &= !BOOT;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+
+    fn lis3dh() -> Lis3dh<FakeDevice> {
+        Lis3dh::new_with_core(FakeDevice::new(), Configuration::default()).unwrap()
+    }
+
+    fn new_sample(lis3dh: &mut Lis3dh<FakeDevice>) {
+        lis3dh.core_mut().new_sample();
+    }
+
+    #[test]
+    fn validate_rejects_temperature_in_low_power() {
+        let conf = Configuration {
+            mode: Mode::LowPower,
+            enable_temperature: true,
+            ..Configuration::default()
+        };
+        assert_eq!(conf.validate(), Err(ConfigError::TemperatureInLowPower));
+
+        assert_eq!(
+            Configuration {
+                enable_temperature: false,
+                ..conf
+            }
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Configuration {
+                mode: Mode::Normal,
+                ..conf
+            }
+            .validate(),
+            Ok(())
+        );
+        assert!(matches!(
+            Lis3dh::new_with_core(FakeDevice::new(), conf),
+            Err(Error::InvalidConfiguration(
+                ConfigError::TemperatureInLowPower
+            ))
+        ));
+    }
+
+    #[test]
+    fn setters_keep_the_configuration_valid() {
+        let mut lis3dh = lis3dh();
+        lis3dh.enable_temp(true).unwrap();
+        let registers = lis3dh.core().registers;
+
+        assert!(matches!(
+            lis3dh.set_mode(Mode::LowPower),
+            Err(Error::InvalidConfiguration(
+                ConfigError::TemperatureInLowPower
+            ))
+        ));
+        assert_eq!(lis3dh.core().registers, registers);
+        assert_eq!(lis3dh.configuration().mode, Mode::HighResolution);
+
+        lis3dh.enable_temp(false).unwrap();
+        lis3dh.set_mode(Mode::LowPower).unwrap();
+        let registers = lis3dh.core().registers;
+
+        assert!(matches!(
+            lis3dh.enable_temp(true),
+            Err(Error::InvalidConfiguration(
+                ConfigError::TemperatureInLowPower
+            ))
+        ));
+        assert_eq!(lis3dh.core().registers, registers);
+        assert!(!lis3dh.configuration().enable_temperature);
+
+        lis3dh.reinitialize().unwrap();
+    }
+
+    #[test]
+    fn rejects_4d_without_direction() {
+        let mut lis3dh = lis3dh();
+
+        for mode in [InterruptMode::OrCombination, InterruptMode::AndCombination] {
+            assert!(matches!(
+                lis3dh.configure_irq_src_and_control(
+                    Interrupt1,
+                    mode,
+                    InterruptConfig::default(),
+                    LatchInterruptRequest::Disable,
+                    Detect4D::Enable,
+                ),
+                Err(Error::InvalidConfiguration(
+                    ConfigError::Detect4DWithoutDirection
+                ))
+            ));
+        }
+        assert_eq!(lis3dh.core().registers[Register::CTRL5.addr() as usize], 0);
+
+        for mode in [InterruptMode::Movement, InterruptMode::Position] {
+            lis3dh
+                .configure_irq_src_and_control(
+                    Interrupt1,
+                    mode,
+                    InterruptConfig::default(),
+                    LatchInterruptRequest::Disable,
+                    Detect4D::Enable,
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_long_durations() {
+        let mut lis3dh = lis3dh();
+        let duration = Duration::seconds(DataRate::Hz_400, 0.5).unwrap();
+        assert_eq!(duration.bits(), 200);
+
+        assert!(matches!(
+            lis3dh.configure_irq_duration(Interrupt1, duration),
+            Err(Error::InvalidConfiguration(ConfigError::DurationOutOfRange))
+        ));
+
+        let duration = Duration::seconds(DataRate::Hz_1, 127.0).unwrap();
+        lis3dh.configure_irq_duration(Interrupt1, duration).unwrap();
+        assert_eq!(lis3dh.get_irq_duration(Interrupt1).unwrap().bits(), 0x7F);
+    }
+
+    #[test]
+    fn reconfigure_powers_down_first() {
+        let core = Traced::new(FakeDevice::new(), TraceBuffer::<8>::new());
+        let mut lis3dh = Lis3dh::new_with_core(core, Configuration::default()).unwrap();
+        lis3dh.core_mut().sink_mut().clear();
+
+        lis3dh
+            .reconfigure(Configuration {
+                mode: Mode::LowPower,
+                datarate: DataRate::Hz_10,
+                ..Configuration::default()
+            })
+            .unwrap();
+
+        let mut writes = lis3dh
+            .core()
+            .sink()
+            .iter()
+            .filter(|transaction| transaction.access == Access::Write);
+        assert_eq!(
+            writes.next(),
+            Some(&Transaction::write(Register::CTRL1, &[0x07]))
+        );
+        assert_eq!(
+            writes.next(),
+            Some(&Transaction::write(
+                Register::TEMP_CFG,
+                &[ADC_EN, 0x0F, 0, 0, BDU, 0, 0]
+            ))
+        );
+        assert_eq!(
+            writes.next(),
+            Some(&Transaction::write(Register::CTRL1, &[0x2F]))
+        );
+        assert_eq!(writes.next(), None);
+    }
+
+    #[test]
+    fn read_sample_flags_stale_data() {
+        let mut lis3dh = lis3dh();
+
+        let sample = lis3dh.read_sample().unwrap();
+        assert_eq!(sample.sequence, 0);
+        assert!(sample.flags.stale);
+
+        new_sample(&mut lis3dh);
+        let sample = lis3dh.read_sample().unwrap();
+        assert_eq!(sample.sequence, 1);
+        assert!(!sample.flags.stale);
+        assert!(sample.flags.settling);
+
+        let sample = lis3dh.read_sample().unwrap();
+        assert_eq!(sample.sequence, 1);
+        assert!(sample.flags.stale);
+        assert_eq!(lis3dh.unsettled_samples(), 6);
+
+        new_sample(&mut lis3dh);
+        assert_eq!(lis3dh.read_sample().unwrap().sequence, 2);
+    }
+
+    /// Adds up the requested delays
+    struct Delay(u32);
+
+    impl DelayUs<u32> for Delay {
+        fn delay_us(&mut self, us: u32) {
+            self.0 += us;
+        }
+    }
+
+    #[test]
+    fn blocking_setters_wait_for_settling() {
+        let mut lis3dh = lis3dh();
+        let mut delay = Delay(0);
+
+        lis3dh
+            .set_datarate_blocking(DataRate::Hz_100, &mut delay)
+            .unwrap();
+        assert_eq!(delay.0, 7 * 10_000);
+        assert!(lis3dh.is_settled());
+
+        lis3dh.set_mode_blocking(Mode::Normal, &mut delay).unwrap();
+        assert_eq!(delay.0, 8 * 10_000);
+
+        lis3dh.set_datarate(DataRate::PowerDown).unwrap();
+        lis3dh.set_range_blocking(Range::G4, &mut delay).unwrap();
+        assert_eq!(delay.0, 8 * 10_000);
+    }
+
+    #[test]
+    fn only_new_samples_settle() {
+        let mut lis3dh = lis3dh();
+        assert_eq!(lis3dh.unsettled_samples(), 7);
+
+        for _ in 0..10 {
+            lis3dh.accel_counts().unwrap();
+        }
+        assert_eq!(lis3dh.unsettled_samples(), 7);
+
+        for _ in 0..7 {
+            new_sample(&mut lis3dh);
+            lis3dh.accel_counts().unwrap();
+            lis3dh.accel_counts().unwrap();
+        }
+        assert!(lis3dh.is_settled());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::register::*;
+use crate::{Error, Lis3dhCore};
+
+/// Register file of a device, reading the outputs clears `ZYXDA` like
+/// the real one.
+pub(crate) struct FakeDevice {
+    pub(crate) registers: [u8; REGISTER_COUNT],
+
+    /// Samples that become available one after the other, each as soon as
+    /// the previous one was read
+    pub(crate) pending: u32,
+}
+
+impl FakeDevice {
+    pub(crate) fn new() -> Self {
+        let mut registers = [0; REGISTER_COUNT];
+        registers[Register::WHOAMI.addr() as usize] = DEVICE_ID;
+
+        Self {
+            registers,
+            pending: 0,
+        }
+    }
+
+    /// Make a new sample available.
+    pub(crate) fn new_sample(&mut self) {
+        self.registers[Register::STATUS.addr() as usize] |= ZYXDA;
+    }
+
+    /// Make `count` new samples available, one after the other.
+    pub(crate) fn queue_samples(&mut self, count: u32) {
+        if count > 0 {
+            self.new_sample();
+            self.pending += count - 1;
+        }
+    }
+}
+
+impl Lis3dhCore for FakeDevice {
+    type BusError = ();
+    type PinError = ();
+
+    fn write_register(&mut self, register: Register, value: u8) -> Result<(), Error<(), ()>> {
+        self.write_registers(register, &[value])
+    }
+
+    fn read_register(&mut self, register: Register) -> Result<u8, Error<(), ()>> {
+        let mut value = [0];
+        self.read_registers(register, &mut value)?;
+
+        Ok(value[0])
+    }
+
+    fn read_registers(&mut self, start: Register, buf: &mut [u8]) -> Result<(), Error<(), ()>> {
+        let start = start.addr() as usize;
+        buf.copy_from_slice(&self.registers[start..start + buf.len()]);
+
+        if (start..start + buf.len()).contains(&(Register::OUT_X_L.addr() as usize)) {
+            self.registers[Register::STATUS.addr() as usize] &= !ZYXDA;
+
+            if self.pending > 0 {
+                self.pending -= 1;
+                self.new_sample();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_registers(&mut self, start: Register, data: &[u8]) -> Result<(), Error<(), ()>> {
+        let start = start.addr() as usize;
+        self.registers[start..start + data.len()].copy_from_slice(data);
+
+        Ok(())
+    }
+}
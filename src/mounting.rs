@@ -0,0 +1,421 @@
+use accelerometer::vector::I16x3;
+
+use crate::ConfigError;
+
+/// A sensor axis, optionally inverted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SignedAxis {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl SignedAxis {
+    const fn new(index: usize, negative: bool) -> Self {
+        match (index, negative) {
+            (0, false) => SignedAxis::PositiveX,
+            (0, true) => SignedAxis::NegativeX,
+            (1, false) => SignedAxis::PositiveY,
+            (1, true) => SignedAxis::NegativeY,
+            (_, false) => SignedAxis::PositiveZ,
+            (_, true) => SignedAxis::NegativeZ,
+        }
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            SignedAxis::PositiveX | SignedAxis::NegativeX => 0,
+            SignedAxis::PositiveY | SignedAxis::NegativeY => 1,
+            SignedAxis::PositiveZ | SignedAxis::NegativeZ => 2,
+        }
+    }
+
+    const fn is_negative(self) -> bool {
+        matches!(
+            self,
+            SignedAxis::NegativeX | SignedAxis::NegativeY | SignedAxis::NegativeZ
+        )
+    }
+}
+
+/// How the sensor is mounted on the board. Samples and interrupt
+/// configurations are transformed between the sensor frame and the board
+/// frame, see [`Lis3dh::set_mounting`].
+///
+/// Example: the sensor is mounted upside-down, rotated around the y axis.
+///
+///     // let mut lis3dh = ...
+///     lis3dh.set_mounting(Mounting::Axes {
+///         x: SignedAxis::NegativeX,
+///         y: SignedAxis::PositiveY,
+///         z: SignedAxis::NegativeZ,
+///     })?;
+///
+/// [`Lis3dh::set_mounting`]: crate::Lis3dh::set_mounting
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mounting {
+    /// The sensor axis measuring each of the board axes. Every sensor axis
+    /// has to be used exactly once.
+    Axes {
+        x: SignedAxis,
+        y: SignedAxis,
+        z: SignedAxis,
+    },
+
+    /// A rotation matrix from the sensor frame to the board frame, so that
+    /// `board = matrix * sensor`. The matrix has to be orthonormal.
+    ///
+    /// Interrupt configurations and sources are mapped like [`Mounting::Axes`]
+    /// when the matrix only permutes and negates axes. Otherwise they can
+    /// only be mapped when they treat all axes alike, and
+    /// [`ConfigError::MountingNotAxisAligned`] is returned for the others.
+    Matrix([[f32; 3]; 3]),
+}
+
+impl Default for Mounting {
+    fn default() -> Self {
+        Mounting::Axes {
+            x: SignedAxis::PositiveX,
+            y: SignedAxis::PositiveY,
+            z: SignedAxis::PositiveZ,
+        }
+    }
+}
+
+/// `INTx_CFG` and `INTx_SRC` axis event bits
+const AXIS_EVENTS: u8 = 0b0011_1111;
+
+/// `INTx_CFG` 6D bit, set for the direction sensitive interrupt modes
+const DIRECTIONAL: u8 = 0b0100_0000;
+
+/// `CLICK_SRC` sign bit
+const CLICK_NEGATIVE: u8 = 0b0000_1000;
+
+/// `CLICK_SRC` axis bits
+const CLICK_AXES: u8 = 0b0000_0111;
+
+/// Tolerance of the orthonormality check, and of matrix entries taken as ±1
+const TOLERANCE: f32 = 1e-3;
+
+impl Mounting {
+    /// Check that every sensor axis is used exactly once, or that the matrix
+    /// is orthonormal.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Mounting::Axes { x, y, z } => {
+                let used = (1 << x.index()) | (1 << y.index()) | (1 << z.index());
+                if used != 0b111 {
+                    return Err(ConfigError::InvalidMounting);
+                }
+
+                Ok(())
+            }
+            Mounting::Matrix(matrix) => {
+                for (i, a) in matrix.iter().enumerate() {
+                    for (j, b) in matrix.iter().enumerate() {
+                        let dot: f32 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+                        let expected = if i == j { 1.0 } else { 0.0 };
+
+                        // Also rejects NaN and infinities
+                        if dot.is_nan() || (dot - expected).abs() > TOLERANCE {
+                            return Err(ConfigError::InvalidMounting);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// The sensor axis of each board axis, also for a matrix that only
+    /// permutes and negates axes.
+    fn axes(&self) -> Option<[SignedAxis; 3]> {
+        match *self {
+            Mounting::Axes { x, y, z } => Some([x, y, z]),
+            Mounting::Matrix(matrix) => {
+                let mut axes = [SignedAxis::PositiveX; 3];
+                for (axis, row) in axes.iter_mut().zip(matrix.iter()) {
+                    // The other entries of an orthonormal row are then zero
+                    let index = row
+                        .iter()
+                        .position(|m| (m.abs() - 1.0).abs() <= TOLERANCE)?;
+                    *axis = SignedAxis::new(index, row[index] < 0.0);
+                }
+
+                Some(axes)
+            }
+        }
+    }
+
+    /// Transform a sample from the sensor frame to the board frame.
+    pub(crate) fn sample_to_board(&self, sensor: I16x3) -> I16x3 {
+        let sensor = [sensor.x, sensor.y, sensor.z];
+
+        let board = match self {
+            Mounting::Axes { x, y, z } => [x, y, z].map(|axis| {
+                let value = sensor[axis.index()];
+                if axis.is_negative() {
+                    value.saturating_neg()
+                } else {
+                    value
+                }
+            }),
+            Mounting::Matrix(matrix) => matrix.map(|row| {
+                let value: f32 = row
+                    .iter()
+                    .zip(sensor.iter())
+                    .map(|(m, s)| m * *s as f32)
+                    .sum();
+
+                // Round half away from zero, `as` saturates
+                if value < 0.0 {
+                    (value - 0.5) as i16
+                } else {
+                    (value + 0.5) as i16
+                }
+            }),
+        };
+
+        I16x3::new(board[0], board[1], board[2])
+    }
+
+    /// Transform per-axis flags from the sensor frame to the board frame. With
+    /// a matrix a board axis is flagged when any sensor axis contributing to
+    /// it is.
+    pub(crate) fn axes_to_board(&self, sensor: [bool; 3]) -> [bool; 3] {
+        match self {
+            Mounting::Axes { x, y, z } => [x, y, z].map(|axis| sensor[axis.index()]),
+            Mounting::Matrix(matrix) => matrix.map(|row| {
+                row.iter()
+                    .zip(sensor.iter())
+                    .any(|(m, flagged)| *m != 0.0 && *flagged)
+            }),
+        }
+    }
+
+    /// Transform the axis events of an `INTx_CFG` value from the board frame
+    /// to the sensor frame.
+    ///
+    /// In the direction sensitive modes a negated axis swaps its high and low
+    /// events, otherwise the events compare the magnitude and only follow
+    /// the permutation. Any other matrix can only map events that are the
+    /// same on every axis, in a mode that is not direction sensitive.
+    pub(crate) fn cfg_to_sensor(&self, cfg: u8) -> Result<u8, ConfigError> {
+        let directional = cfg & DIRECTIONAL != 0;
+
+        match self.axes() {
+            Some(axes) => {
+                let mut sensor = cfg & !AXIS_EVENTS;
+                for (board, axis) in axes.iter().enumerate() {
+                    let events = axis_events(cfg, board, directional && axis.is_negative());
+                    sensor |= events << (2 * axis.index());
+                }
+
+                Ok(sensor)
+            }
+            None => uniform_events(cfg, cfg),
+        }
+    }
+
+    /// Transform the axis events of an `INTx_CFG` or `INTx_SRC` value from
+    /// the sensor frame to the board frame, given the `INTx_CFG` value that
+    /// selects the interrupt mode. Any other matrix than a permutation can
+    /// only map events that are the same on every axis, see
+    /// [`Mounting::cfg_to_sensor`].
+    pub(crate) fn src_to_board(&self, cfg: u8, src: u8) -> Result<u8, ConfigError> {
+        let directional = cfg & DIRECTIONAL != 0;
+
+        match self.axes() {
+            Some(axes) => {
+                let mut board = src & !AXIS_EVENTS;
+                for (index, axis) in axes.iter().enumerate() {
+                    let events = axis_events(src, axis.index(), directional && axis.is_negative());
+                    board |= events << (2 * index);
+                }
+
+                Ok(board)
+            }
+            None => uniform_events(cfg, src),
+        }
+    }
+
+    /// Transform a `CLICK_SRC` value from the sensor frame to the board
+    /// frame. Any other matrix than a permutation can only map a value
+    /// without axis bits.
+    pub(crate) fn click_to_board(&self, src: u8) -> Result<u8, ConfigError> {
+        match self.axes() {
+            Some(axes) => {
+                let mut board = src & !(CLICK_AXES | CLICK_NEGATIVE);
+                let mut negative = src & CLICK_NEGATIVE != 0;
+                for (index, axis) in axes.iter().enumerate() {
+                    if src & (1 << axis.index()) != 0 {
+                        board |= 1 << index;
+                        negative ^= axis.is_negative();
+                    }
+                }
+
+                Ok(board | if negative { CLICK_NEGATIVE } else { 0 })
+            }
+            None if src & CLICK_AXES == 0 => Ok(src),
+            None => Err(ConfigError::MountingNotAxisAligned),
+        }
+    }
+}
+
+/// Keep axis events that are the same on every axis, in a mode that is not
+/// direction sensitive. These do not depend on the mounting.
+fn uniform_events(cfg: u8, bits: u8) -> Result<u8, ConfigError> {
+    let events = bits & 0b11;
+    let uniform = events * 0b01_0101 == bits & AXIS_EVENTS;
+
+    if cfg & DIRECTIONAL != 0 || !uniform {
+        return Err(ConfigError::MountingNotAxisAligned);
+    }
+
+    Ok(bits)
+}
+
+/// The low (bit 0) and high (bit 1) events of an axis, optionally swapped.
+const fn axis_events(bits: u8, axis: usize, swap: bool) -> u8 {
+    let events = (bits >> (2 * axis)) & 0b11;
+
+    if swap {
+        (events & 0b01) << 1 | (events >> 1)
+    } else {
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rotated by 90° around the z axis
+    const ROTATED: Mounting = Mounting::Axes {
+        x: SignedAxis::PositiveY,
+        y: SignedAxis::NegativeX,
+        z: SignedAxis::PositiveZ,
+    };
+
+    const ROTATED_MATRIX: Mounting =
+        Mounting::Matrix([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    /// Rotated by 45° around the z axis
+    const DIAGONAL: Mounting = Mounting::Matrix([
+        [
+            core::f32::consts::FRAC_1_SQRT_2,
+            -core::f32::consts::FRAC_1_SQRT_2,
+            0.0,
+        ],
+        [
+            core::f32::consts::FRAC_1_SQRT_2,
+            core::f32::consts::FRAC_1_SQRT_2,
+            0.0,
+        ],
+        [0.0, 0.0, 1.0],
+    ]);
+
+    #[test]
+    fn validate() {
+        assert_eq!(ROTATED.validate(), Ok(()));
+        assert_eq!(ROTATED_MATRIX.validate(), Ok(()));
+        assert_eq!(DIAGONAL.validate(), Ok(()));
+        assert_eq!(
+            Mounting::Matrix([[-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]).validate(),
+            Ok(())
+        );
+
+        let invalid = [
+            Mounting::Axes {
+                x: SignedAxis::PositiveX,
+                y: SignedAxis::NegativeX,
+                z: SignedAxis::PositiveZ,
+            },
+            Mounting::Matrix([[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 2.0]]),
+            Mounting::Matrix([[1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]),
+            Mounting::Matrix([[f32::NAN, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            Mounting::Matrix([[f32::INFINITY, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+        ];
+        for mounting in invalid.iter() {
+            assert_eq!(mounting.validate(), Err(ConfigError::InvalidMounting));
+        }
+    }
+
+    #[test]
+    fn sample_to_board() {
+        let sample = I16x3::new(1, 2, 3);
+        assert_eq!(ROTATED.sample_to_board(sample), I16x3::new(2, -1, 3));
+        assert_eq!(ROTATED_MATRIX.sample_to_board(sample), I16x3::new(2, -1, 3));
+
+        let sample = I16x3::new(i16::MIN, 0, 0);
+        assert_eq!(ROTATED.sample_to_board(sample), I16x3::new(0, i16::MAX, 0));
+
+        let sample = I16x3::new(100, 0, -7);
+        assert_eq!(DIAGONAL.sample_to_board(sample), I16x3::new(71, 71, -7));
+    }
+
+    #[test]
+    fn cfg_to_sensor() {
+        // OR combination, board y high: sensor x high
+        assert_eq!(ROTATED.cfg_to_sensor(0b0000_1000), Ok(0b0000_0010));
+
+        // Movement, board x and y high: sensor y high and sensor x low
+        assert_eq!(ROTATED.cfg_to_sensor(0b0100_1010), Ok(0b0100_1001));
+        assert_eq!(ROTATED_MATRIX.cfg_to_sensor(0b0100_1010), Ok(0b0100_1001));
+    }
+
+    #[test]
+    fn src_to_board_inverts_cfg_to_sensor() {
+        for mounting in [Mounting::default(), ROTATED, ROTATED_MATRIX].iter() {
+            for cfg in 0..=u8::MAX {
+                let sensor = mounting.cfg_to_sensor(cfg).unwrap();
+                assert_eq!(mounting.src_to_board(sensor, sensor), Ok(cfg));
+            }
+        }
+    }
+
+    #[test]
+    fn click_to_board() {
+        // Positive click on sensor x, negative on board y
+        assert_eq!(ROTATED.click_to_board(0b0101_0001), Ok(0b0101_1010));
+        assert_eq!(ROTATED_MATRIX.click_to_board(0b0101_0001), Ok(0b0101_1010));
+        // Negative click on sensor y, negative on board x
+        assert_eq!(ROTATED.click_to_board(0b0101_1010), Ok(0b0101_1001));
+    }
+
+    #[test]
+    fn matrix_maps_uniform_events_only() {
+        // AND combination of all low events, as used for free-fall
+        assert_eq!(DIAGONAL.cfg_to_sensor(0b1001_0101), Ok(0b1001_0101));
+        assert_eq!(
+            DIAGONAL.src_to_board(0b1001_0101, 0b0101_0101),
+            Ok(0b0101_0101)
+        );
+        assert_eq!(DIAGONAL.src_to_board(0, 0), Ok(0));
+
+        assert_eq!(
+            DIAGONAL.cfg_to_sensor(0b0000_0010),
+            Err(ConfigError::MountingNotAxisAligned)
+        );
+        assert_eq!(
+            DIAGONAL.cfg_to_sensor(0b1101_0101),
+            Err(ConfigError::MountingNotAxisAligned)
+        );
+        assert_eq!(
+            DIAGONAL.src_to_board(0b0000_0010, 0b0100_0010),
+            Err(ConfigError::MountingNotAxisAligned)
+        );
+
+        assert_eq!(DIAGONAL.click_to_board(0b0001_0000), Ok(0b0001_0000));
+        assert_eq!(
+            DIAGONAL.click_to_board(0b0101_0001),
+            Err(ConfigError::MountingNotAxisAligned)
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::interrupts::{Interrupt, InterruptSource};
+use crate::{Error, Lis3dh, Lis3dhCore};
+
+/// Orientation of the device as recognised by the 6D position detection.
+///
+/// The orientation is named after the axis that points up, i.e. the axis
+/// that measures +1g or -1g at rest:
+///
+/// | Axis | Orientation      |
+/// |:-----|:-----------------|
+/// | +Y   | `PortraitUp`     |
+/// | -Y   | `PortraitDown`   |
+/// | +X   | `LandscapeLeft`  |
+/// | -X   | `LandscapeRight` |
+/// | +Z   | `FaceUp`         |
+/// | -Z   | `FaceDown`       |
+///
+/// With 4D detection enabled only the portrait and landscape orientations are
+/// recognised.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Orientation {
+    PortraitUp,
+    PortraitDown,
+    LandscapeLeft,
+    LandscapeRight,
+    FaceUp,
+    FaceDown,
+}
+
+impl Orientation {
+    /// Decode an interrupt source configured in [`InterruptMode::Position`]
+    /// mode. Returns `None` unless exactly one axis event is flagged.
+    ///
+    /// [`InterruptMode::Position`]: crate::InterruptMode::Position
+    pub const fn from_source(source: InterruptSource) -> Option<Self> {
+        match (
+            source.x_axis_high,
+            source.x_axis_low,
+            source.y_axis_high,
+            source.y_axis_low,
+            source.z_axis_high,
+            source.z_axis_low,
+        ) {
+            (false, false, true, false, false, false) => Some(Orientation::PortraitUp),
+            (false, false, false, true, false, false) => Some(Orientation::PortraitDown),
+            (true, false, false, false, false, false) => Some(Orientation::LandscapeLeft),
+            (false, true, false, false, false, false) => Some(Orientation::LandscapeRight),
+            (false, false, false, false, true, false) => Some(Orientation::FaceUp),
+            (false, false, false, false, false, true) => Some(Orientation::FaceDown),
+            _ => None,
+        }
+    }
+}
+
+/// Reports orientation changes once the same orientation has been read a
+/// number of times in a row, filtering out the short-lived positions the
+/// device passes through while being turned.
+///
+/// This is a hardware-assisted alternative to [`accelerometer::Tracker`]:
+/// the position recognition runs on the device and only the interrupt
+/// source register is read.
+///
+///     // let mut lis3dh = ...
+///     lis3dh.configure_irq_threshold(Interrupt1, Threshold::g(Range::G2, 0.5)?)?;
+///     lis3dh.configure_irq_src(Interrupt1, InterruptMode::Position, InterruptConfig::high_and_low())?;
+///
+///     let mut debouncer = OrientationDebouncer::new(3);
+///     loop {
+///         if let Some(orientation) = debouncer.poll(&mut lis3dh, Interrupt1)? {
+///             // the orientation changed
+///         }
+///     }
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OrientationDebouncer {
+    current: Option<Orientation>,
+    candidate: Option<Orientation>,
+    count: u8,
+    required: u8,
+}
+
+impl OrientationDebouncer {
+    /// Create a debouncer reporting a change after `required` identical
+    /// readings in a row.
+    pub const fn new(required: u8) -> Self {
+        Self {
+            current: None,
+            candidate: None,
+            count: 0,
+            required,
+        }
+    }
+
+    /// The last reported orientation.
+    pub const fn orientation(&self) -> Option<Orientation> {
+        self.current
+    }
+
+    /// Update with a new reading. Returns the new orientation when it
+    /// changed. Readings without a recognised orientation restart the count.
+    pub fn update(&mut self, reading: Option<Orientation>) -> Option<Orientation> {
+        if reading != self.candidate {
+            self.candidate = reading;
+            self.count = 0;
+        }
+
+        let orientation = self.candidate?;
+        self.count = self.count.saturating_add(1);
+
+        if self.count >= self.required && self.current != Some(orientation) {
+            self.current = Some(orientation);
+            return Some(orientation);
+        }
+
+        None
+    }
+
+    /// Read the orientation of interrupt generator `int` and update the
+    /// debouncer. See [`Lis3dh::get_orientation`].
+    pub fn poll<CORE, I>(
+        &mut self,
+        lis3dh: &mut Lis3dh<CORE>,
+        int: I,
+    ) -> Result<Option<Orientation>, Error<CORE::BusError, CORE::PinError>>
+    where
+        CORE: Lis3dhCore,
+        I: Interrupt,
+    {
+        let reading = lis3dh.get_orientation(int)?;
+
+        Ok(self.update(reading))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interrupts::Interrupt1;
+    use crate::mock::FakeDevice;
+    use crate::register::Register;
+    use crate::Configuration;
+
+    const IA: u8 = 0x40;
+
+    fn orientation(src: u8) -> Option<Orientation> {
+        Orientation::from_source(InterruptSource::from_bits(src))
+    }
+
+    #[test]
+    fn from_source() {
+        assert_eq!(orientation(IA | 0b0000_1000), Some(Orientation::PortraitUp));
+        assert_eq!(
+            orientation(IA | 0b0000_0100),
+            Some(Orientation::PortraitDown)
+        );
+        assert_eq!(
+            orientation(IA | 0b0000_0010),
+            Some(Orientation::LandscapeLeft)
+        );
+        assert_eq!(
+            orientation(IA | 0b0000_0001),
+            Some(Orientation::LandscapeRight)
+        );
+        assert_eq!(orientation(IA | 0b0010_0000), Some(Orientation::FaceUp));
+        assert_eq!(orientation(IA | 0b0001_0000), Some(Orientation::FaceDown));
+    }
+
+    #[test]
+    fn requires_exactly_one_axis_event() {
+        assert_eq!(orientation(IA), None);
+        assert_eq!(orientation(IA | 0b0000_1010), None);
+        assert_eq!(orientation(IA | 0b0011_0000), None);
+        assert_eq!(orientation(IA | 0b0011_1111), None);
+    }
+
+    #[test]
+    fn debouncer_waits_for_the_hold_count() {
+        let mut debouncer = OrientationDebouncer::new(3);
+        assert_eq!(debouncer.update(Some(Orientation::FaceUp)), None);
+        assert_eq!(debouncer.update(Some(Orientation::FaceUp)), None);
+        assert_eq!(debouncer.orientation(), None);
+
+        assert_eq!(
+            debouncer.update(Some(Orientation::FaceUp)),
+            Some(Orientation::FaceUp)
+        );
+        assert_eq!(debouncer.orientation(), Some(Orientation::FaceUp));
+
+        // reported once
+        assert_eq!(debouncer.update(Some(Orientation::FaceUp)), None);
+    }
+
+    #[test]
+    fn debouncer_restarts_the_count() {
+        let mut debouncer = OrientationDebouncer::new(2);
+        assert_eq!(debouncer.update(Some(Orientation::PortraitUp)), None);
+        assert_eq!(debouncer.update(Some(Orientation::LandscapeLeft)), None);
+        assert_eq!(debouncer.update(None), None);
+        assert_eq!(debouncer.update(Some(Orientation::LandscapeLeft)), None);
+        assert_eq!(debouncer.orientation(), None);
+
+        assert_eq!(
+            debouncer.update(Some(Orientation::LandscapeLeft)),
+            Some(Orientation::LandscapeLeft)
+        );
+
+        // passing through another orientation keeps the current one
+        assert_eq!(debouncer.update(Some(Orientation::FaceUp)), None);
+        assert_eq!(debouncer.update(Some(Orientation::LandscapeLeft)), None);
+        assert_eq!(debouncer.update(Some(Orientation::LandscapeLeft)), None);
+        assert_eq!(debouncer.orientation(), Some(Orientation::LandscapeLeft));
+    }
+
+    #[test]
+    fn debouncer_without_hold_count() {
+        let mut debouncer = OrientationDebouncer::new(0);
+        assert_eq!(
+            debouncer.update(Some(Orientation::FaceDown)),
+            Some(Orientation::FaceDown)
+        );
+        assert_eq!(debouncer.update(None), None);
+        assert_eq!(debouncer.orientation(), Some(Orientation::FaceDown));
+    }
+
+    #[test]
+    fn poll() {
+        let mut lis3dh =
+            Lis3dh::new_with_core(FakeDevice::new(), Configuration::default()).unwrap();
+        let mut debouncer = OrientationDebouncer::new(2);
+
+        // not in position mode
+        lis3dh.core_mut().registers[Register::INT1_SRC.addr() as usize] = IA | 0b0010_0000;
+        assert_eq!(lis3dh.get_orientation(Interrupt1).unwrap(), None);
+
+        lis3dh.core_mut().registers[Register::INT1_CFG.addr() as usize] = 0b1111_1111;
+        assert_eq!(
+            lis3dh.get_orientation(Interrupt1).unwrap(),
+            Some(Orientation::FaceUp)
+        );
+
+        assert_eq!(debouncer.poll(&mut lis3dh, Interrupt1).unwrap(), None);
+        assert_eq!(
+            debouncer.poll(&mut lis3dh, Interrupt1).unwrap(),
+            Some(Orientation::FaceUp)
+        );
+    }
+}
@@ -1,7 +1,12 @@
+use core::convert::TryFrom;
+
 use num_enum::TryFromPrimitive;
 
+use crate::ConfigError;
+
 /// Possible I²C slave addresses.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum SlaveAddr {
     /// Default slave address (`0x18`)
@@ -19,7 +24,8 @@ impl SlaveAddr {
 
 /// Enumerate all device registers.
 #[allow(dead_code, non_camel_case_types, clippy::upper_case_acronyms)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Register {
     STATUS_AUX = 0x07,
@@ -97,11 +103,44 @@ impl Register {
                 | Register::CLICK_SRC
         )
     }
+
+    /// Does reading the register clear it? The interrupt sources are cleared
+    /// on read when the interrupt is latched.
+    pub fn clears_on_read(self) -> bool {
+        matches!(
+            self,
+            Register::INT1_SRC | Register::INT2_SRC | Register::CLICK_SRC
+        )
+    }
+
+    /// Do any of the `len` registers starting at `start` clear on read?
+    pub(crate) fn clears_on_read_range(start: Register, len: usize) -> bool {
+        (start.addr() as usize..start.addr() as usize + len).any(|addr| {
+            match u8::try_from(addr).map(Register::try_from) {
+                Ok(Ok(register)) => register.clears_on_read(),
+                _ => false,
+            }
+        })
+    }
+
+    /// Are all `len` registers starting at `start` writable?
+    pub fn writable_range(start: Register, len: usize) -> bool {
+        (start.addr() as usize..start.addr() as usize + len).all(|addr| {
+            match u8::try_from(addr).map(Register::try_from) {
+                Ok(Ok(register)) => !register.read_only(),
+                _ => false,
+            }
+        })
+    }
 }
 
+/// Size of the register address space
+pub(crate) const REGISTER_COUNT: usize = 0x40;
+
 /// Full-scale selection.
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Range {
     /// ±16g
@@ -140,33 +179,51 @@ impl Default for Range {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Threshold(pub(crate) u8);
 
 impl Threshold {
+    /// Raw register value
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
     /// Convert a value in multiples of the `g` constant (roughly 9.81) to a threshold.
+    /// Returns [`ConfigError::ThresholdOutOfRange`] if the value is negative, not finite or
+    /// does not fit in the 7 bit threshold registers for the given range.
     ///
-    ///     assert_eq!(Threshold::g(Range::G2, 1.1), 69);
+    ///     assert_eq!(Threshold::g(Range::G2, 1.1).unwrap(), 69);
     #[inline(always)]
-    pub fn g(range: Range, gs: f32) -> Self {
+    pub fn g(range: Range, gs: f32) -> Result<Self, ConfigError> {
         Self::mg(range, gs * 1000.0)
     }
 
+    /// Convert a value in mili-g to a threshold. See [`Threshold::g`].
     #[inline(always)]
-    pub fn mg(range: Range, mgs: f32) -> Self {
+    pub fn mg(range: Range, mgs: f32) -> Result<Self, ConfigError> {
         let value = mgs / (range.as_mg() as f32);
+        if !value.is_finite() || value < 0.0 {
+            return Err(ConfigError::ThresholdOutOfRange);
+        }
+
         let truncated = value as u64;
 
         let round_up = value - (truncated as f32) > 0.5;
 
         let result = if round_up { truncated + 1 } else { truncated };
 
-        Threshold(result as u8)
+        if result > 0b0111_1111 {
+            return Err(ConfigError::ThresholdOutOfRange);
+        }
+
+        Ok(Threshold(result as u8))
     }
 }
 
 /// Output data rate.
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum DataRate {
     /// 400Hz (Default)
@@ -211,28 +268,54 @@ impl DataRate {
             DataRate::PowerDown => 0.0,
         }
     }
+
+    /// Sample period in microseconds, zero when powered down. Avoids
+    /// floating point arithmetic on targets without an FPU.
+    pub(crate) const fn period_us(self) -> u32 {
+        match self {
+            DataRate::Hz_400 => 2_500,
+            DataRate::Hz_200 => 5_000,
+            DataRate::Hz_100 => 10_000,
+            DataRate::Hz_50 => 20_000,
+            DataRate::Hz_25 => 40_000,
+            DataRate::Hz_10 => 100_000,
+            DataRate::Hz_1 => 1_000_000,
+            DataRate::PowerDown => 0,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Duration(pub(crate) u8);
 
 impl Duration {
+    /// Raw register value
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
     /// Convert a number of seconds into a duration. Internally a duration is represented
     /// as a multiple of `1 / ODR` where ODR (the output data rate) is of type [`DataRate`].
+    /// Returns [`ConfigError::DurationOutOfRange`] if the value is negative or
+    /// does not fit in a `u8`.
     #[inline(always)]
-    pub fn seconds(output_data_rate: DataRate, seconds: f32) -> Self {
+    pub fn seconds(output_data_rate: DataRate, seconds: f32) -> Result<Self, ConfigError> {
         let duration = output_data_rate.sample_rate() * seconds;
+        if !(0.0..256.0).contains(&duration) {
+            return Err(ConfigError::DurationOutOfRange);
+        }
 
-        Self(duration as u8)
+        Ok(Self(duration as u8))
     }
 
     /// Convert a number of miliseconds into a duration. Internally a duration is represented
     /// as a multiple of `1 / ODR` where ODR (the output data rate) is of type [`DataRate`].
     ///
-    ///     assert_eq!(Duration::miliseconds(DataRate::Hz_400, 25.0), 10);
+    ///     assert_eq!(Duration::miliseconds(DataRate::Hz_400, 25.0).unwrap(), 10);
     #[inline(always)]
-    pub fn miliseconds(output_data_rate: DataRate, miliseconds: f32) -> Self {
-        Self::seconds(output_data_rate, miliseconds * 1000.0)
+    pub fn miliseconds(output_data_rate: DataRate, miliseconds: f32) -> Result<Self, ConfigError> {
+        Self::seconds(output_data_rate, miliseconds / 1000.0)
     }
 }
 
@@ -254,6 +337,7 @@ impl Duration {
 ///  * `zyxda` -> `ZYXDA`
 ///  * `xyzda` -> (`XDA`, `YDA`, `ZDA`)
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DataStatus {
     /// ZYXOR bit
     pub zyxor: bool,
@@ -270,6 +354,7 @@ pub struct DataStatus {
 
 /// Operating mode.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Mode {
     /// High-resolution mode (12-bit data output)
@@ -282,6 +367,49 @@ pub enum Mode {
     LowPower,
 }
 
+impl Mode {
+    /// Number of bits the left-justified output needs to be shifted right by
+    /// for the resolution of this mode.
+    pub const fn shift(self) -> u8 {
+        match self {
+            Mode::HighResolution => 4, // High Resolution:  12-bit
+            Mode::Normal => 6,         // Normal:           10-bit
+            Mode::LowPower => 8,       // Low Power:         8-bit
+        }
+    }
+
+    /// Sensitivity in mg per digit of the right-justified output for the
+    /// given range. See "2.1 Mechanical characteristics" in the datasheet.
+    pub const fn sensitivity_mg(self, range: Range) -> u8 {
+        match (self, range) {
+            // High Resolution mode
+            (Mode::HighResolution, Range::G2) => 1,
+            (Mode::HighResolution, Range::G4) => 2,
+            (Mode::HighResolution, Range::G8) => 4,
+            (Mode::HighResolution, Range::G16) => 12,
+            // Normal mode
+            (Mode::Normal, Range::G2) => 4,
+            (Mode::Normal, Range::G4) => 8,
+            (Mode::Normal, Range::G8) => 16,
+            (Mode::Normal, Range::G16) => 48,
+            // Low Power mode
+            (Mode::LowPower, Range::G2) => 16,
+            (Mode::LowPower, Range::G4) => 32,
+            (Mode::LowPower, Range::G8) => 64,
+            (Mode::LowPower, Range::G16) => 192,
+        }
+    }
+
+    /// Number of samples needed for the output to settle after switching to
+    /// this mode or changing the data rate while in it.
+    pub const fn settling_samples(self) -> u8 {
+        match self {
+            Mode::HighResolution => 7,
+            Mode::Normal | Mode::LowPower => 1,
+        }
+    }
+}
+
 // === WHO_AMI_I (0Fh) ===
 
 /// `WHO_AM_I` device identification register
@@ -300,12 +428,25 @@ pub const Z_EN: u8 = 0b0000_0100;
 pub const Y_EN: u8 = 0b0000_0010;
 pub const X_EN: u8 = 0b0000_0001;
 
+// === CTRL_REG3 (22h) ===
+
+pub const I1_ZYXDA: u8 = 0b0001_0000;
+
 // === CTRL_REG4 (23h) ===
 
 pub const BDU: u8 = 0b1000_0000;
 pub const FS_MASK: u8 = 0b0011_0000;
 pub const HR: u8 = 0b0000_1000;
 
+// === CTRL_REG5 (24h) ===
+
+pub const BOOT: u8 = 0b1000_0000;
+
+// === CTRL_REG6 (25h) ===
+
+pub const I2_ACT: u8 = 0b0000_1000;
+pub const H_LACTIVE: u8 = 0b0000_0010;
+
 // === STATUS_REG (27h) ===
 
 pub const ZYXOR: u8 = 0b1000_0000;
@@ -316,3 +457,63 @@ pub const ZYXDA: u8 = 0b0000_1000;
 pub const ZDA: u8 = 0b0000_0100;
 pub const YDA: u8 = 0b0000_0010;
 pub const XDA: u8 = 0b0000_0001;
+
+// === FIFO_SRC_REG (2Fh) ===
+
+pub const WTM: u8 = 0b1000_0000;
+pub const OVRN_FIFO: u8 = 0b0100_0000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_from_g() {
+        assert_eq!(Threshold::g(Range::G2, 1.1).unwrap().bits(), 69);
+        assert_eq!(Threshold::mg(Range::G16, 0.0).unwrap().bits(), 0);
+        assert_eq!(Threshold::mg(Range::G2, 127.0 * 16.0).unwrap().bits(), 127);
+    }
+
+    #[test]
+    fn threshold_out_of_range() {
+        for mg in [f32::NAN, -1.0, 128.0 * 16.0, f32::INFINITY] {
+            assert_eq!(
+                Threshold::mg(Range::G2, mg),
+                Err(ConfigError::ThresholdOutOfRange)
+            );
+        }
+        assert_eq!(
+            Threshold::g(Range::G16, 24.0),
+            Err(ConfigError::ThresholdOutOfRange)
+        );
+    }
+
+    #[test]
+    fn duration_from_seconds() {
+        assert_eq!(Duration::seconds(DataRate::Hz_100, 0.5).unwrap().bits(), 50);
+        assert_eq!(
+            Duration::miliseconds(DataRate::Hz_400, 25.0)
+                .unwrap()
+                .bits(),
+            10
+        );
+        assert_eq!(
+            Duration::seconds(DataRate::Hz_1, 255.0).unwrap().bits(),
+            255
+        );
+    }
+
+    #[test]
+    fn duration_out_of_range() {
+        for seconds in [f32::NAN, -0.1, 256.0, f32::INFINITY] {
+            assert_eq!(
+                Duration::seconds(DataRate::Hz_1, seconds),
+                Err(ConfigError::DurationOutOfRange)
+            );
+        }
+        assert_eq!(
+            Duration::miliseconds(DataRate::Hz_400, 1000.0),
+            Err(ConfigError::DurationOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use core::convert::{Infallible, TryFrom};
+use core::fmt::{self, Display};
+
+use crate::register::Register;
+use crate::trace::{Access, TraceSink, Transaction, TRACE_DATA};
+use crate::{Error, Lis3dhCore};
+
+// Recording layout, all values little-endian:
+//
+// | Size | Content                                    |
+// |:-----|:-------------------------------------------|
+// | 4    | `MAGIC`                                    |
+// | 1    | `VERSION`                                  |
+//
+// followed by one entry per transaction:
+//
+// | Size             | Content                                    |
+// |:-----------------|:-------------------------------------------|
+// | 1                | flags, `WRITE`, `FAILED` and `TIMESTAMP`   |
+// | 1                | register address                           |
+// | 1                | length `n`                                 |
+// | 4                | timestamp, if `TIMESTAMP` is set           |
+// | min(n, 16)       | data, zero for a failed read               |
+
+const MAGIC: [u8; 4] = *b"L3TR";
+const VERSION: u8 = 1;
+
+const WRITE: u8 = 0b001;
+const FAILED: u8 = 0b010;
+const TIMESTAMP: u8 = 0b100;
+
+/// Destination of a recording, e.g. a file or an RTT channel. Implemented
+/// for [`RecordBuffer`], and for `std::io::Write` with the `std` feature.
+pub trait ByteSink {
+    type Error;
+
+    /// Write all of `bytes`.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "std")]
+impl<W> ByteSink for W
+where
+    W: std::io::Write,
+{
+    type Error = std::io::Error;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_all(bytes)
+    }
+}
+
+/// A fixed-size [`ByteSink`] in memory, failing when full.
+#[derive(Debug, Clone)]
+pub struct RecordBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> RecordBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The bytes written so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> Default for RecordBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ByteSink for RecordBuffer<N> {
+    type Error = ();
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let end = self.len + bytes.len();
+        if end > N {
+            return Err(());
+        }
+
+        self.bytes[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+/// A [`TraceSink`] writing the transactions recorded by
+/// [`Traced`](crate::Traced) to a [`ByteSink`], to be served again by
+/// [`Replay`].
+///
+///     let file = std::fs::File::create("session.l3tr")?;
+///     let core = Traced::new(Lis3dhI2C::new(i2c, SlaveAddr::Default), Recorder::new(file));
+///     let mut lis3dh = Lis3dh::new_with_core(core, Configuration::default())?;
+///
+/// Only the first [`TRACE_DATA`] bytes of longer bursts are recorded, reads
+/// of those can not be replayed.
+pub struct Recorder<W>
+where
+    W: ByteSink,
+{
+    writer: W,
+    error: Option<W::Error>,
+}
+
+impl<W> Recorder<W>
+where
+    W: ByteSink,
+{
+    /// Create a recorder, writing the header of the recording.
+    pub fn new(mut writer: W) -> Self {
+        let mut header = [0u8; 5];
+        header[..4].copy_from_slice(&MAGIC);
+        header[4] = VERSION;
+        let error = writer.write_bytes(&header).err();
+
+        Self { writer, error }
+    }
+
+    /// The first write error, after which nothing more is recorded.
+    pub fn error(&self) -> Option<&W::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Destroy the recorder and return the writer.
+    pub fn release(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> TraceSink for Recorder<W>
+where
+    W: ByteSink,
+{
+    fn record(&mut self, transaction: &Transaction) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let mut entry = [0u8; 7 + TRACE_DATA];
+        let mut flags = 0;
+        if transaction.access == Access::Write {
+            flags |= WRITE;
+        }
+        if !transaction.ok {
+            flags |= FAILED;
+        }
+        entry[1] = transaction.register.addr();
+        entry[2] = transaction.len;
+
+        let mut len = 3;
+        if let Some(timestamp) = transaction.timestamp {
+            flags |= TIMESTAMP;
+            entry[3..7].copy_from_slice(&timestamp.to_le_bytes());
+            len += 4;
+        }
+        entry[0] = flags;
+
+        let data = transaction.data();
+        entry[len..len + data.len()].copy_from_slice(data);
+        len += data.len();
+
+        self.error = self.writer.write_bytes(&entry[..len]).err();
+    }
+}
+
+/// Errors of the [`Replay`] transport.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReplayError {
+    /// The recording does not start with a supported header
+    InvalidRecording,
+
+    /// The entry of transaction `index` can not be decoded
+    Corrupt { index: u32 },
+
+    /// Transaction `index` is not the recorded one. For a read only the
+    /// register and the length of `actual` are meaningful.
+    Mismatch {
+        index: u32,
+        expected: Transaction,
+        actual: Transaction,
+    },
+
+    /// Transaction `index` was requested after the end of the recording
+    Exhausted { index: u32, actual: Transaction },
+
+    /// Transaction `index` reads more bytes than were recorded
+    Truncated { index: u32 },
+
+    /// Transaction `index` failed when it was recorded
+    Failed { index: u32 },
+
+    /// The recording continues with transaction `index`, see
+    /// [`Replay::finish`]
+    Unused { index: u32 },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::InvalidRecording => f.write_str("not a supported bus recording"),
+            ReplayError::Corrupt { index } => {
+                write!(f, "recording is corrupt at transaction {}", index)
+            }
+            ReplayError::Mismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "transaction {} does not match the recording\n  expected: {}\n  actual:   {}",
+                index,
+                Request(expected),
+                Request(actual)
+            ),
+            ReplayError::Exhausted { index, actual } => write!(
+                f,
+                "transaction {} is past the end of the recording: {}",
+                index,
+                Request(actual)
+            ),
+            ReplayError::Truncated { index } => {
+                write!(f, "transaction {} was recorded truncated", index)
+            }
+            ReplayError::Failed { index } => {
+                write!(f, "transaction {} failed when recorded", index)
+            }
+            ReplayError::Unused { index } => {
+                write!(f, "recording continues with unused transaction {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+/// Formats a transaction without the data of reads.
+struct Request<'a>(&'a Transaction);
+
+impl Display for Request<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let transaction = self.0;
+
+        match transaction.access {
+            Access::Read => write!(
+                f,
+                "read  {:?} ({:#04x}), {} bytes",
+                transaction.register,
+                transaction.register.addr(),
+                transaction.len
+            ),
+            Access::Write => Display::fmt(
+                &Transaction {
+                    timestamp: None,
+                    ..*transaction
+                },
+                f,
+            ),
+        }
+    }
+}
+
+/// A transport serving the responses of a recording made with [`Recorder`],
+/// for deterministic tests of code built on [`Lis3dh`](crate::Lis3dh).
+///
+/// Every transaction has to match the recorded one, otherwise it fails with
+/// [`ReplayError::Mismatch`] describing both.
+///
+///     let recording = std::fs::read("session.l3tr")?;
+///     let mut lis3dh = Lis3dh::new_with_core(Replay::new(&recording)?, Configuration::default())?;
+///     assert_eq!(my_application(&mut lis3dh)?, expected);
+///     lis3dh.core().finish()?;
+pub struct Replay<'a> {
+    recording: &'a [u8],
+    position: usize,
+    index: u32,
+}
+
+impl<'a> Replay<'a> {
+    /// Create a transport replaying `recording`.
+    pub fn new(recording: &'a [u8]) -> Result<Self, ReplayError> {
+        if recording.len() < 5 || recording[..4] != MAGIC || recording[4] != VERSION {
+            return Err(ReplayError::InvalidRecording);
+        }
+
+        Ok(Self {
+            recording,
+            position: 5,
+            index: 0,
+        })
+    }
+
+    /// Number of transactions served so far.
+    pub fn transactions(&self) -> u32 {
+        self.index
+    }
+
+    /// Check that every recorded transaction has been served.
+    pub fn finish(&self) -> Result<(), ReplayError> {
+        if self.position < self.recording.len() {
+            return Err(ReplayError::Unused { index: self.index });
+        }
+
+        Ok(())
+    }
+
+    /// Decode the next entry, returning it and the position after it.
+    fn peek(&self) -> Result<Option<(Transaction, usize)>, ReplayError> {
+        let corrupt = ReplayError::Corrupt { index: self.index };
+
+        let rest = &self.recording[self.position..];
+        let (flags, addr, len) = match rest {
+            [] => return Ok(None),
+            [flags, addr, len, ..] => (*flags, *addr, *len),
+            _ => return Err(corrupt),
+        };
+        if flags & !(WRITE | FAILED | TIMESTAMP) != 0 {
+            return Err(corrupt);
+        }
+        let register = Register::try_from(addr).map_err(|_| corrupt)?;
+
+        let mut offset = 3;
+        let mut timestamp = None;
+        if flags & TIMESTAMP != 0 {
+            let bytes = rest.get(3..7).ok_or(corrupt)?;
+            timestamp = Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            offset += 4;
+        }
+
+        let kept = (len as usize).min(TRACE_DATA);
+        let data = rest.get(offset..offset + kept).ok_or(corrupt)?;
+        let mut transaction = if flags & WRITE != 0 {
+            Transaction::write(register, data)
+        } else {
+            Transaction::read(register, data)
+        };
+        transaction.len = len;
+        transaction.ok = flags & FAILED == 0;
+        transaction.timestamp = timestamp;
+
+        Ok(Some((transaction, self.position + offset + kept)))
+    }
+
+    /// Match `actual` against the next entry and serve it.
+    fn next(&mut self, actual: Transaction) -> Result<Transaction, Error<ReplayError, Infallible>> {
+        let index = self.index;
+        let (expected, next) = self
+            .peek()?
+            .ok_or(ReplayError::Exhausted { index, actual })?;
+
+        let matches = expected.access == actual.access
+            && expected.register == actual.register
+            && expected.len == actual.len
+            && (actual.access == Access::Read || expected.data() == actual.data());
+        if !matches {
+            return Err(ReplayError::Mismatch {
+                index,
+                expected,
+                actual,
+            }
+            .into());
+        }
+
+        self.position = next;
+        self.index += 1;
+
+        if !expected.ok {
+            return Err(ReplayError::Failed { index }.into());
+        }
+        if expected.is_truncated() && expected.access == Access::Read {
+            return Err(ReplayError::Truncated { index }.into());
+        }
+
+        Ok(expected)
+    }
+}
+
+impl From<ReplayError> for Error<ReplayError, Infallible> {
+    fn from(error: ReplayError) -> Self {
+        Error::Bus(error)
+    }
+}
+
+impl Lis3dhCore for Replay<'_> {
+    type BusError = ReplayError;
+    type PinError = Infallible;
+
+    fn write_register(
+        &mut self,
+        register: Register,
+        value: u8,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.next(Transaction::write(register, &[value]))?;
+
+        Ok(())
+    }
+
+    fn read_register(
+        &mut self,
+        register: Register,
+    ) -> Result<u8, Error<Self::BusError, Self::PinError>> {
+        let served = self.next(Transaction::read(register, &[0]))?;
+
+        Ok(served.data()[0])
+    }
+
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        let mut actual = Transaction::read(start, &[]);
+        actual.len = buf.len().min(u8::MAX as usize) as u8;
+
+        let served = self.next(actual)?;
+        buf.copy_from_slice(served.data());
+
+        Ok(())
+    }
+
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.next(Transaction::write(start, data))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::{Configuration, DataRate, Lis3dh, Traced};
+
+    type Recording = Recorder<RecordBuffer<512>>;
+
+    /// Initialize a device and read a sample, recording the bus traffic.
+    fn record() -> Lis3dh<Traced<FakeDevice, Recording>> {
+        let mut device = FakeDevice::new();
+        device.registers[Register::OUT_X_L.addr() as usize..][..6]
+            .copy_from_slice(&[0x10, 0x20, 0x30, 0x40, 0x50, 0x60]);
+
+        let core = Traced::new(device, Recorder::new(RecordBuffer::new()));
+        let mut lis3dh = Lis3dh::new_with_core(core, Configuration::default()).unwrap();
+        lis3dh.accel_counts().unwrap();
+
+        assert!(lis3dh.core().sink().error().is_none());
+        lis3dh
+    }
+
+    fn recording(lis3dh: &Lis3dh<Traced<FakeDevice, Recording>>) -> &[u8] {
+        lis3dh.core().sink().writer().as_bytes()
+    }
+
+    fn bus_error<T>(result: Result<T, Error<ReplayError, Infallible>>) -> ReplayError {
+        match result {
+            Err(Error::Bus(error)) => error,
+            _ => panic!("expected a replay error"),
+        }
+    }
+
+    #[test]
+    fn replays_recording() {
+        let recorded = record();
+        let replay = Replay::new(recording(&recorded)).unwrap();
+        let mut lis3dh = Lis3dh::new_with_core(replay, Configuration::default()).unwrap();
+
+        let counts = lis3dh.accel_counts().unwrap();
+        assert_eq!((counts.x, counts.y, counts.z), (0x201, 0x403, 0x605));
+        assert_eq!(lis3dh.core().transactions(), 8);
+        lis3dh.core().finish().unwrap();
+    }
+
+    #[test]
+    fn reports_mismatch() {
+        let recorded = record();
+        let replay = Replay::new(recording(&recorded)).unwrap();
+        let configuration = Configuration {
+            datarate: DataRate::Hz_1,
+            ..Configuration::default()
+        };
+
+        let error = bus_error(Lis3dh::new_with_core(replay, configuration));
+        assert_eq!(
+            error,
+            ReplayError::Mismatch {
+                index: 4,
+                expected: Transaction::write(Register::CTRL1, &[0x77]),
+                actual: Transaction::write(Register::CTRL1, &[0x17]),
+            }
+        );
+
+        let mut text = [0u8; 128];
+        let mut cursor = Cursor(&mut text, 0);
+        fmt::write(&mut cursor, format_args!("{}", error)).unwrap();
+        let len = cursor.1;
+        assert_eq!(
+            core::str::from_utf8(&text[..len]).unwrap(),
+            "transaction 4 does not match the recording\n  \
+             expected: write CTRL1 (0x20): 77\n  \
+             actual:   write CTRL1 (0x20): 17"
+        );
+    }
+
+    #[test]
+    fn reports_read_mismatch() {
+        let recorded = record();
+        let mut replay = Replay::new(recording(&recorded)).unwrap();
+
+        let error = bus_error(replay.read_registers(Register::WHOAMI, &mut [0; 2]));
+        let mut actual = Transaction::read(Register::WHOAMI, &[0, 0]);
+        actual.bytes = [0; TRACE_DATA];
+        assert_eq!(
+            error,
+            ReplayError::Mismatch {
+                index: 0,
+                expected: Transaction::read(Register::WHOAMI, &[0x33]),
+                actual,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_exhausted_and_unused() {
+        let recorded = record();
+        let replay = Replay::new(recording(&recorded)).unwrap();
+        let mut lis3dh = Lis3dh::new_with_core(replay, Configuration::default()).unwrap();
+
+        assert_eq!(
+            lis3dh.core().finish(),
+            Err(ReplayError::Unused { index: 5 })
+        );
+
+        lis3dh.accel_counts().unwrap();
+        assert_eq!(
+            bus_error(lis3dh.get_device_id()),
+            ReplayError::Exhausted {
+                index: 8,
+                actual: Transaction::read(Register::WHOAMI, &[0]),
+            }
+        );
+    }
+
+    #[test]
+    fn replays_recorded_failures() {
+        let mut recorder = Recorder::new(RecordBuffer::<64>::new());
+        let mut failed = Transaction::read(Register::STATUS, &[0]);
+        failed.ok = false;
+        recorder.record(&failed);
+
+        let mut replay = Replay::new(recorder.writer().as_bytes()).unwrap();
+        assert_eq!(
+            bus_error(replay.read_register(Register::STATUS)),
+            ReplayError::Failed { index: 0 }
+        );
+        replay.finish().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_recordings() {
+        assert_eq!(
+            Replay::new(b"L3TR").err(),
+            Some(ReplayError::InvalidRecording)
+        );
+
+        let mut replay = Replay::new(b"L3TR\x01\x00\x01").unwrap();
+        assert_eq!(
+            bus_error(replay.read_register(Register::STATUS)),
+            ReplayError::Corrupt { index: 0 }
+        );
+    }
+
+    /// Formats into a byte buffer without allocating.
+    struct Cursor<'a>(&'a mut [u8], usize);
+
+    impl fmt::Write for Cursor<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.1 + s.len();
+            self.0
+                .get_mut(self.1..end)
+                .ok_or(fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.1 = end;
+
+            Ok(())
+        }
+    }
+}
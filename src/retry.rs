@@ -0,0 +1,328 @@
+use embedded_hal::blocking::delay::DelayUs;
+
+use crate::register::Register;
+use crate::{Error, Lis3dhCore};
+
+/// How often and how fast [`Retry`] retries failed transactions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    /// Total number of attempts of a transaction, default `3`
+    pub attempts: u8,
+
+    /// Delay before the first retry in microseconds, default `100`. The
+    /// delay doubles with every further retry.
+    pub backoff_us: u32,
+
+    /// Upper limit of the delay in microseconds, default `10_000`
+    pub max_backoff_us: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff_us: 100,
+            max_backoff_us: 10_000,
+        }
+    }
+}
+
+/// Transaction and error counts of a [`Retry`] transport.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryCounters {
+    /// Transactions started
+    pub transactions: u32,
+
+    /// Attempts repeated after a bus or pin error
+    pub retries: u32,
+
+    /// Transactions that succeeded after a retry
+    pub recovered: u32,
+
+    /// Transactions that failed on every attempt
+    pub failed: u32,
+}
+
+/// A transport wrapper retrying transactions that fail with a bus or pin
+/// error, see [`Lis3dh::new_with_core`].
+///
+/// Every [`Lis3dhCore`] method is retried as a whole, so a
+/// [`Lis3dhCore::modify_register`] reads the register again before writing
+/// it. Reads covering a register that is cleared on read, like `INT1_SRC`,
+/// are not retried, as a failed attempt may already have cleared the latched
+/// events. See [`Register::clears_on_read`]. Neither are reads of
+/// `STATUS_REG` together with the outputs, as reading the outputs clears
+/// `ZYXDA` and a repeated read would report the sample as stale.
+///
+/// [`Lis3dh::new_with_core`]: crate::Lis3dh::new_with_core
+pub struct Retry<CORE, D> {
+    core: CORE,
+    delay: D,
+    policy: RetryPolicy,
+    counters: RetryCounters,
+}
+
+impl<CORE, D> Retry<CORE, D>
+where
+    CORE: Lis3dhCore,
+    D: DelayUs<u32>,
+{
+    pub fn new(core: CORE, delay: D, policy: RetryPolicy) -> Self {
+        Self {
+            core,
+            delay,
+            policy,
+            counters: RetryCounters::default(),
+        }
+    }
+
+    pub fn counters(&self) -> RetryCounters {
+        self.counters
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.counters = RetryCounters::default();
+    }
+
+    /// Destroy the wrapper and return the transport and the delay.
+    pub fn release(self) -> (CORE, D) {
+        (self.core, self.delay)
+    }
+
+    /// Run `transaction`, retrying it if it is `idempotent`.
+    fn retry<T, F>(
+        &mut self,
+        idempotent: bool,
+        mut transaction: F,
+    ) -> Result<T, Error<CORE::BusError, CORE::PinError>>
+    where
+        F: FnMut(&mut CORE) -> Result<T, Error<CORE::BusError, CORE::PinError>>,
+    {
+        self.counters.transactions = self.counters.transactions.wrapping_add(1);
+
+        let attempts = if idempotent { self.policy.attempts } else { 1 };
+        let mut backoff = self.policy.backoff_us;
+        let mut attempt = 1;
+        loop {
+            match transaction(&mut self.core) {
+                Ok(value) => {
+                    if attempt > 1 {
+                        self.counters.recovered = self.counters.recovered.wrapping_add(1);
+                    }
+
+                    return Ok(value);
+                }
+                Err(Error::Bus(_)) | Err(Error::Pin(_)) if attempt < attempts => {
+                    self.counters.retries = self.counters.retries.wrapping_add(1);
+
+                    self.delay.delay_us(backoff);
+                    backoff = backoff.saturating_mul(2).min(self.policy.max_backoff_us);
+                    attempt += 1;
+                }
+                Err(error) => {
+                    if matches!(error, Error::Bus(_) | Error::Pin(_)) {
+                        self.counters.failed = self.counters.failed.wrapping_add(1);
+                    }
+
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+impl<CORE, D> Lis3dhCore for Retry<CORE, D>
+where
+    CORE: Lis3dhCore,
+    D: DelayUs<u32>,
+{
+    type BusError = CORE::BusError;
+    type PinError = CORE::PinError;
+
+    fn write_register(
+        &mut self,
+        register: Register,
+        value: u8,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.retry(true, |core| core.write_register(register, value))
+    }
+
+    fn read_register(
+        &mut self,
+        register: Register,
+    ) -> Result<u8, Error<Self::BusError, Self::PinError>> {
+        self.retry(!register.clears_on_read(), |core| {
+            core.read_register(register)
+        })
+    }
+
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.retry(is_repeatable(start, buf.len()), |core| {
+            core.read_registers(start, buf)
+        })
+    }
+
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        self.retry(true, |core| core.write_registers(start, data))
+    }
+
+    fn modify_register<F>(
+        &mut self,
+        register: Register,
+        f: F,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>>
+    where
+        F: Fn(u8) -> u8,
+    {
+        self.retry(true, |core| core.modify_register(register, &f))
+    }
+
+    fn read_accel_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
+        self.retry(true, |core| core.read_accel_bytes())
+    }
+
+    fn read_adc_bytes(&mut self) -> Result<[u8; 6], Error<Self::BusError, Self::PinError>> {
+        self.retry(true, |core| core.read_adc_bytes())
+    }
+}
+
+/// Can a read of `len` registers starting at `start` be repeated without
+/// losing state?
+fn is_repeatable(start: Register, len: usize) -> bool {
+    let first = start.addr() as usize;
+    let end = first + len;
+    let status_with_outputs =
+        first <= Register::STATUS.addr() as usize && end > Register::OUT_X_L.addr() as usize;
+
+    !status_with_outputs && !Register::clears_on_read_range(start, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails the first `failures` transactions with a bus error.
+    struct Flaky {
+        failures: u8,
+        attempts: u8,
+    }
+
+    impl Flaky {
+        fn attempt(&mut self) -> Result<(), Error<(), ()>> {
+            self.attempts += 1;
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(Error::Bus(()));
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Lis3dhCore for Flaky {
+        type BusError = ();
+        type PinError = ();
+
+        fn write_register(&mut self, _: Register, _: u8) -> Result<(), Error<(), ()>> {
+            self.attempt()
+        }
+
+        fn read_register(&mut self, _: Register) -> Result<u8, Error<(), ()>> {
+            self.attempt().map(|_| 0)
+        }
+
+        fn read_registers(&mut self, _: Register, _: &mut [u8]) -> Result<(), Error<(), ()>> {
+            self.attempt()
+        }
+
+        fn write_registers(&mut self, _: Register, _: &[u8]) -> Result<(), Error<(), ()>> {
+            self.attempt()
+        }
+    }
+
+    struct NoDelay;
+
+    impl DelayUs<u32> for NoDelay {
+        fn delay_us(&mut self, _: u32) {}
+    }
+
+    fn flaky(failures: u8) -> Retry<Flaky, NoDelay> {
+        let flaky = Flaky {
+            failures,
+            attempts: 0,
+        };
+
+        Retry::new(flaky, NoDelay, RetryPolicy::default())
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let mut retry = flaky(2);
+
+        assert!(retry.read_register(Register::CTRL1).is_ok());
+        assert_eq!(retry.core.attempts, 3);
+        assert_eq!(
+            retry.counters(),
+            RetryCounters {
+                transactions: 1,
+                retries: 2,
+                recovered: 1,
+                failed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn gives_up_after_all_attempts() {
+        let mut retry = flaky(5);
+
+        assert!(retry.write_register(Register::CTRL1, 0).is_err());
+        assert_eq!(retry.core.attempts, 3);
+        assert_eq!(retry.counters().failed, 1);
+    }
+
+    #[test]
+    fn does_not_retry_clear_on_read() {
+        let mut retry = flaky(1);
+        assert!(retry.read_register(Register::INT1_SRC).is_err());
+        assert_eq!(retry.core.attempts, 1);
+
+        // FIFO_SRC to CLICK_SRC, as read by `poll_events`
+        let mut retry = flaky(1);
+        assert!(retry
+            .read_registers(Register::FIFO_SRC, &mut [0; 11])
+            .is_err());
+        assert_eq!(retry.core.attempts, 1);
+        assert_eq!(retry.counters().failed, 1);
+
+        let mut retry = flaky(1);
+        assert!(retry.read_registers(Register::STATUS, &mut [0; 1]).is_ok());
+        assert_eq!(retry.core.attempts, 2);
+    }
+
+    #[test]
+    fn does_not_retry_status_with_outputs() {
+        // STATUS_REG followed by the outputs, as read by `read_sample`
+        let mut retry = flaky(1);
+        assert!(retry.read_registers(Register::STATUS, &mut [0; 7]).is_err());
+        assert_eq!(retry.core.attempts, 1);
+
+        let mut retry = flaky(1);
+        assert!(retry.read_registers(Register::OUT_X_L, &mut [0; 6]).is_ok());
+        assert_eq!(retry.core.attempts, 2);
+
+        let mut retry = flaky(1);
+        assert!(retry.read_registers(Register::CTRL1, &mut [0; 7]).is_ok());
+        assert_eq!(retry.core.attempts, 2);
+    }
+}
@@ -0,0 +1,54 @@
+use accelerometer::vector::I32x3;
+
+/// A sample with its sequence number and data-quality flags, see
+/// [`Lis3dh::read_sample`].
+///
+/// [`Lis3dh::read_sample`]: crate::Lis3dh::read_sample
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    /// Acceleration in milli-g, in the board frame
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub accel_mg: I32x3,
+
+    /// Number of new samples read with [`Lis3dh::read_sample`] up to and
+    /// including this one, wrapping around, so sequence numbers start at 1.
+    /// A stale sample has the number of the sample it repeats, or 0 when no
+    /// new sample was read yet.
+    ///
+    /// [`Lis3dh::read_sample`]: crate::Lis3dh::read_sample
+    pub sequence: u32,
+
+    pub flags: SampleFlags,
+}
+
+/// Data-quality flags of a [`Sample`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SampleFlags {
+    /// A new sample overwrote the previous one before it was read, `ZYXOR`
+    pub overrun: bool,
+
+    /// An axis is at the limit of the range
+    pub saturated: bool,
+
+    /// The outputs had not settled after a mode, data rate or range change,
+    /// see [`Lis3dh::is_settled`]
+    ///
+    /// [`Lis3dh::is_settled`]: crate::Lis3dh::is_settled
+    pub settling: bool,
+
+    /// No new data was available, `ZYXDA` was clear, so this is the previous
+    /// sample read again
+    pub stale: bool,
+
+    /// (x, y, z) axes that are disabled in the board frame, and read as zero
+    pub disabled: (bool, bool, bool),
+}
+
+impl SampleFlags {
+    /// Is none of the flags set?
+    pub fn is_good(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -0,0 +1,386 @@
+use core::convert::TryFrom;
+use core::fmt::{self, Debug, Display};
+
+use embedded_storage::nor_flash::{
+    check_erase, check_read, check_write, ErrorType, NorFlash, NorFlashErrorKind, ReadNorFlash,
+};
+
+use crate::crc::crc32;
+use crate::{Calibration, ConfigError, Configuration};
+
+/// Settings that are kept in flash with [`Settings::store`] and
+/// [`Settings::load`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Settings {
+    pub configuration: Configuration,
+    pub calibration: Calibration,
+}
+
+/// Errors while loading or storing [`Settings`].
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StorageError<E> {
+    /// Flash error
+    Flash(E),
+
+    /// No record found, e.g. the flash is erased
+    NotFound,
+
+    /// The record length or checksum does not match
+    Corrupt,
+
+    /// The record was written by a newer version of this crate
+    UnsupportedVersion(u8),
+
+    /// The stored configuration is invalid
+    InvalidConfiguration(ConfigError),
+
+    /// The offset or the flash write and erase sizes do not allow storing a
+    /// record
+    Alignment,
+}
+
+impl<E> Display for StorageError<E>
+where
+    E: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Flash(error) => write!(f, "flash error: {:?}", error),
+            StorageError::NotFound => f.write_str("no settings record found"),
+            StorageError::Corrupt => f.write_str("settings record is corrupt"),
+            StorageError::UnsupportedVersion(version) => {
+                write!(f, "unsupported settings record version {}", version)
+            }
+            StorageError::InvalidConfiguration(error) => {
+                write!(f, "invalid stored configuration: {}", error)
+            }
+            StorageError::Alignment => f.write_str("settings record does not fit the flash layout"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for StorageError<E> where E: Debug {}
+
+impl<E> From<ConfigError> for StorageError<E> {
+    fn from(error: ConfigError) -> Self {
+        StorageError::InvalidConfiguration(error)
+    }
+}
+
+// Record layout, all values little-endian:
+//
+// | Offset  | Size | Content                         |
+// |:--------|:-----|:--------------------------------|
+// | 0       | 4    | `MAGIC`                         |
+// | 4       | 1    | version                         |
+// | 5       | 1    | payload length `n`              |
+// | 6       | n    | payload                         |
+// | 6 + n   | 4    | CRC-32 of the preceding bytes   |
+//
+// Version 1 payloads only hold the configuration, version 2 payloads add the
+// calibration.
+
+const MAGIC: [u8; 4] = *b"LIS3";
+const VERSION: u8 = 2;
+const HEADER: usize = 6;
+const CRC: usize = 4;
+const PAYLOAD_V1: usize = Configuration::BYTES;
+const PAYLOAD_V2: usize = Configuration::BYTES + Calibration::BYTES;
+const RECORD: usize = HEADER + PAYLOAD_V2 + CRC;
+
+/// Size of the buffer used for reads and writes, records are padded to the
+/// flash read and write sizes within it
+const BUFFER: usize = 128;
+
+impl Settings {
+    /// Write the settings to flash at `offset`, which has to be aligned to the
+    /// flash erase size. The record is rewritten in place, erasing the sectors
+    /// it occupies.
+    pub fn store<F>(&self, flash: &mut F, offset: u32) -> Result<(), StorageError<F::Error>>
+    where
+        F: NorFlash,
+    {
+        let length = round_up(RECORD, F::WRITE_SIZE).ok_or(StorageError::Alignment)?;
+        if offset as usize / F::ERASE_SIZE * F::ERASE_SIZE != offset as usize {
+            return Err(StorageError::Alignment);
+        }
+
+        let erase_length = length.div_ceil(F::ERASE_SIZE) * F::ERASE_SIZE;
+        let erase_end = u32::try_from(erase_length)
+            .ok()
+            .and_then(|erase_length| offset.checked_add(erase_length))
+            .ok_or(StorageError::Alignment)?;
+
+        let mut buffer = [0xFF; BUFFER];
+        buffer[0..4].copy_from_slice(&MAGIC);
+        buffer[4] = VERSION;
+        buffer[5] = PAYLOAD_V2 as u8;
+        buffer[HEADER..HEADER + Configuration::BYTES]
+            .copy_from_slice(&self.configuration.to_bytes());
+        buffer[HEADER + Configuration::BYTES..HEADER + PAYLOAD_V2]
+            .copy_from_slice(&self.calibration.to_bytes());
+        let crc = crc32(&buffer[..HEADER + PAYLOAD_V2]);
+        buffer[HEADER + PAYLOAD_V2..RECORD].copy_from_slice(&crc.to_le_bytes());
+
+        flash
+            .erase(offset, erase_end)
+            .map_err(StorageError::Flash)?;
+        flash
+            .write(offset, &buffer[..length])
+            .map_err(StorageError::Flash)
+    }
+
+    /// Read the settings from flash at `offset`, migrating records written by
+    /// older versions. Version 1 records get the default calibration.
+    pub fn load<F>(flash: &mut F, offset: u32) -> Result<Self, StorageError<F::Error>>
+    where
+        F: ReadNorFlash,
+    {
+        let length = round_up(RECORD, F::READ_SIZE).ok_or(StorageError::Alignment)?;
+        let length = length.min(flash.capacity().saturating_sub(offset as usize));
+
+        let mut buffer = [0u8; BUFFER];
+        flash
+            .read(offset, &mut buffer[..length])
+            .map_err(StorageError::Flash)?;
+
+        if buffer[0..4] != MAGIC {
+            return Err(StorageError::NotFound);
+        }
+
+        let version = buffer[4];
+        let payload = buffer[5] as usize;
+        let expected = match version {
+            1 => PAYLOAD_V1,
+            2 => PAYLOAD_V2,
+            _ => return Err(StorageError::UnsupportedVersion(version)),
+        };
+        if payload != expected || HEADER + payload + CRC > length {
+            return Err(StorageError::Corrupt);
+        }
+
+        let (record, rest) = buffer.split_at(HEADER + payload);
+        let crc = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        if crc != crc32(record) {
+            return Err(StorageError::Corrupt);
+        }
+
+        let payload = &record[HEADER..];
+        let mut configuration = [0u8; Configuration::BYTES];
+        configuration.copy_from_slice(&payload[..Configuration::BYTES]);
+
+        let calibration = if version >= 2 {
+            let mut calibration = [0u8; Calibration::BYTES];
+            calibration.copy_from_slice(&payload[Configuration::BYTES..PAYLOAD_V2]);
+            Calibration::from_bytes(&calibration)?
+        } else {
+            Calibration::default()
+        };
+
+        Ok(Settings {
+            configuration: Configuration::from_bytes(&configuration)?,
+            calibration,
+        })
+    }
+}
+
+/// Round `value` up to a multiple of `size`, `None` if that does not fit the
+/// buffer.
+fn round_up(value: usize, size: usize) -> Option<usize> {
+    let rounded = value.div_ceil(size) * size;
+
+    if rounded <= BUFFER {
+        Some(rounded)
+    } else {
+        None
+    }
+}
+
+/// In-memory NOR flash, to try out [`Settings::store`] and [`Settings::load`]
+/// on the host.
+///
+/// Like real NOR flash, writes can only clear bits and erasing sets all
+/// bytes of a sector to `0xFF`.
+///
+///     let mut flash = MemoryFlash::<1024>::new();
+///     settings.store(&mut flash, 0)?;
+///     assert_eq!(Settings::load(&mut flash, 0)?, settings);
+pub struct MemoryFlash<const SIZE: usize> {
+    data: [u8; SIZE],
+}
+
+impl<const SIZE: usize> MemoryFlash<SIZE> {
+    /// Create an erased flash.
+    pub const fn new() -> Self {
+        Self { data: [0xFF; SIZE] }
+    }
+
+    /// The current flash contents.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Mutable access to the flash contents, e.g. to simulate corruption.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+impl<const SIZE: usize> Default for MemoryFlash<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> ErrorType for MemoryFlash<SIZE> {
+    type Error = NorFlashErrorKind;
+}
+
+impl<const SIZE: usize> ReadNorFlash for MemoryFlash<SIZE> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        SIZE
+    }
+}
+
+impl<const SIZE: usize> NorFlash for MemoryFlash<SIZE> {
+    const WRITE_SIZE: usize = 4;
+    const ERASE_SIZE: usize = 256;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+
+        self.data[from as usize..to as usize].fill(0xFF);
+
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+
+        let offset = offset as usize;
+        for (stored, byte) in self.data[offset..offset + bytes.len()]
+            .iter_mut()
+            .zip(bytes)
+        {
+            *stored &= *byte;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataRate, Mode};
+
+    fn settings() -> Settings {
+        Settings {
+            configuration: Configuration {
+                mode: Mode::Normal,
+                datarate: DataRate::Hz_100,
+                enable_y_axis: false,
+                ..Configuration::default()
+            },
+            calibration: Calibration {
+                offset: [0.01, -0.02, 0.03],
+                ..Calibration::default()
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut flash = MemoryFlash::<1024>::new();
+        settings().store(&mut flash, 256).unwrap();
+
+        assert_eq!(Settings::load(&mut flash, 256).unwrap(), settings());
+        assert!(matches!(
+            Settings::load(&mut flash, 0),
+            Err(StorageError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn store_overwrites() {
+        let mut flash = MemoryFlash::<1024>::new();
+        Settings::default().store(&mut flash, 0).unwrap();
+        settings().store(&mut flash, 0).unwrap();
+
+        assert_eq!(Settings::load(&mut flash, 0).unwrap(), settings());
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        let configuration = settings().configuration;
+
+        let mut flash = MemoryFlash::<1024>::new();
+        let record = flash.as_bytes_mut();
+        record[0..4].copy_from_slice(&MAGIC);
+        record[4] = 1;
+        record[5] = PAYLOAD_V1 as u8;
+        record[HEADER..HEADER + PAYLOAD_V1].copy_from_slice(&configuration.to_bytes());
+        let crc = crc32(&record[..HEADER + PAYLOAD_V1]);
+        record[HEADER + PAYLOAD_V1..HEADER + PAYLOAD_V1 + CRC].copy_from_slice(&crc.to_le_bytes());
+
+        let loaded = Settings::load(&mut flash, 0).unwrap();
+        assert_eq!(loaded.configuration, configuration);
+        assert_eq!(loaded.calibration, Calibration::default());
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let mut flash = MemoryFlash::<1024>::new();
+        settings().store(&mut flash, 0).unwrap();
+        flash.as_bytes_mut()[HEADER + 10] ^= 0x01;
+
+        assert!(matches!(
+            Settings::load(&mut flash, 0),
+            Err(StorageError::Corrupt)
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut flash = MemoryFlash::<1024>::new();
+        settings().store(&mut flash, 0).unwrap();
+        flash.as_bytes_mut()[4] = 3;
+
+        assert!(matches!(
+            Settings::load(&mut flash, 0),
+            Err(StorageError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn rejects_misaligned_offsets() {
+        let mut flash = MemoryFlash::<1024>::new();
+
+        assert!(matches!(
+            settings().store(&mut flash, 4),
+            Err(StorageError::Alignment)
+        ));
+        assert!(matches!(
+            settings().store(&mut flash, 1024),
+            Err(StorageError::Flash(_))
+        ));
+        assert!(matches!(
+            settings().store(&mut flash, u32::MAX - 255),
+            Err(StorageError::Alignment)
+        ));
+    }
+}
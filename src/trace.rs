@@ -0,0 +1,437 @@
+use core::fmt::{self, Display};
+
+use crate::register::Register;
+use crate::{Error, Lis3dhCore};
+
+/// Number of data bytes kept per [`Transaction`], longer bursts are
+/// truncated
+pub const TRACE_DATA: usize = 16;
+
+/// Direction of a [`Transaction`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A register access recorded by [`Traced`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Transaction {
+    pub access: Access,
+
+    /// First register of the access
+    pub register: Register,
+
+    /// Number of bytes transferred
+    pub len: u8,
+
+    /// The first [`TRACE_DATA`] bytes read or written. Zero for a failed
+    /// read.
+    pub bytes: [u8; TRACE_DATA],
+
+    /// Did the access succeed?
+    pub ok: bool,
+
+    /// Time of the access, if [`Traced::with_clock`] was used
+    pub timestamp: Option<u32>,
+}
+
+impl Transaction {
+    /// A successful read of `data` starting at `register`, e.g. to compare
+    /// against recorded transactions.
+    pub fn read(register: Register, data: &[u8]) -> Self {
+        Self::new(Access::Read, register, data, true, None)
+    }
+
+    /// A successful write of `data` starting at `register`.
+    pub fn write(register: Register, data: &[u8]) -> Self {
+        Self::new(Access::Write, register, data, true, None)
+    }
+
+    fn new(
+        access: Access,
+        register: Register,
+        data: &[u8],
+        ok: bool,
+        timestamp: Option<u32>,
+    ) -> Self {
+        let mut bytes = [0u8; TRACE_DATA];
+        let kept = data.len().min(TRACE_DATA);
+        bytes[..kept].copy_from_slice(&data[..kept]);
+
+        Self {
+            access,
+            register,
+            len: data.len().min(u8::MAX as usize) as u8,
+            bytes,
+            ok,
+            timestamp,
+        }
+    }
+
+    /// The recorded data bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[..(self.len as usize).min(TRACE_DATA)]
+    }
+
+    /// Were some of the bytes not recorded?
+    pub fn is_truncated(&self) -> bool {
+        self.len as usize > TRACE_DATA
+    }
+}
+
+/// Decodes the transaction using register names, e.g.
+/// `write CTRL1 (0x20): 77`.
+impl Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "[{}] ", timestamp)?;
+        }
+
+        let access = match self.access {
+            Access::Read => "read ",
+            Access::Write => "write",
+        };
+        write!(
+            f,
+            "{} {:?} ({:#04x}):",
+            access,
+            self.register,
+            self.register.addr()
+        )?;
+
+        for byte in self.data() {
+            write!(f, " {:02x}", byte)?;
+        }
+        if self.is_truncated() {
+            write!(f, " ... ({} bytes)", self.len)?;
+        }
+        if !self.ok {
+            f.write_str(" failed")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Receives the transactions recorded by [`Traced`]. Implemented for
+/// closures and [`TraceBuffer`].
+pub trait TraceSink {
+    fn record(&mut self, transaction: &Transaction);
+}
+
+impl<F> TraceSink for F
+where
+    F: FnMut(&Transaction),
+{
+    fn record(&mut self, transaction: &Transaction) {
+        self(transaction)
+    }
+}
+
+/// Ring buffer keeping the last `N` transactions.
+#[derive(Debug, Clone)]
+pub struct TraceBuffer<const N: usize> {
+    entries: [Option<Transaction>; N],
+    next: usize,
+    total: u32,
+}
+
+impl<const N: usize> TraceBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            next: 0,
+            total: 0,
+        }
+    }
+
+    /// The kept transactions, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Transaction> + '_ {
+        let (newer, older) = self.entries.split_at(self.next);
+
+        older.iter().chain(newer.iter()).filter_map(Option::as_ref)
+    }
+
+    /// Number of transactions recorded since creation or the last
+    /// [`TraceBuffer::clear`], including the ones no longer kept.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for TraceBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TraceSink for TraceBuffer<N> {
+    fn record(&mut self, transaction: &Transaction) {
+        self.total = self.total.wrapping_add(1);
+
+        if N > 0 {
+            self.entries[self.next] = Some(*transaction);
+            self.next = (self.next + 1) % N;
+        }
+    }
+}
+
+/// A transport wrapper recording every register access to a [`TraceSink`],
+/// see [`Lis3dh::new_with_core`].
+///
+///     let core = Traced::new(Lis3dhI2C::new(i2c, SlaveAddr::Default), TraceBuffer::<32>::new());
+///     let mut lis3dh = Lis3dh::new_with_core(core, Configuration::default())?;
+///
+///     for transaction in lis3dh.core().sink().iter() {
+///         println!("{}", transaction);
+///     }
+///
+/// Wrap it in [`Retry`] rather than the other way around to see every
+/// attempt.
+///
+/// [`Lis3dh::new_with_core`]: crate::Lis3dh::new_with_core
+/// [`Retry`]: crate::Retry
+pub struct Traced<CORE, SINK> {
+    core: CORE,
+    sink: SINK,
+    clock: Option<fn() -> u32>,
+}
+
+impl<CORE, SINK> Traced<CORE, SINK>
+where
+    CORE: Lis3dhCore,
+    SINK: TraceSink,
+{
+    pub fn new(core: CORE, sink: SINK) -> Self {
+        Self {
+            core,
+            sink,
+            clock: None,
+        }
+    }
+
+    /// Timestamp the transactions with the given clock, e.g. a microsecond
+    /// counter.
+    pub fn with_clock(mut self, clock: fn() -> u32) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn sink(&self) -> &SINK {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut SINK {
+        &mut self.sink
+    }
+
+    /// Destroy the wrapper and return the transport and the sink.
+    pub fn release(self) -> (CORE, SINK) {
+        (self.core, self.sink)
+    }
+
+    fn record<T, E>(
+        &mut self,
+        access: Access,
+        register: Register,
+        data: &[u8],
+        result: &Result<T, E>,
+    ) {
+        let timestamp = self.clock.map(|clock| clock());
+        let transaction = Transaction::new(access, register, data, result.is_ok(), timestamp);
+
+        self.sink.record(&transaction);
+    }
+}
+
+impl<CORE, SINK> Lis3dhCore for Traced<CORE, SINK>
+where
+    CORE: Lis3dhCore,
+    SINK: TraceSink,
+{
+    type BusError = CORE::BusError;
+    type PinError = CORE::PinError;
+
+    fn write_register(
+        &mut self,
+        register: Register,
+        value: u8,
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        let result = self.core.write_register(register, value);
+        self.record(Access::Write, register, &[value], &result);
+
+        result
+    }
+
+    fn read_register(
+        &mut self,
+        register: Register,
+    ) -> Result<u8, Error<Self::BusError, Self::PinError>> {
+        let result = self.core.read_register(register);
+        let value = *result.as_ref().unwrap_or(&0);
+        self.record(Access::Read, register, &[value], &result);
+
+        result
+    }
+
+    fn read_registers(
+        &mut self,
+        start: Register,
+        buf: &mut [u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        let result = self.core.read_registers(start, buf);
+        if result.is_err() {
+            buf.fill(0);
+        }
+        self.record(Access::Read, start, buf, &result);
+
+        result
+    }
+
+    fn write_registers(
+        &mut self,
+        start: Register,
+        data: &[u8],
+    ) -> Result<(), Error<Self::BusError, Self::PinError>> {
+        let result = self.core.write_registers(start, data);
+        self.record(Access::Write, start, data, &result);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use core::fmt::Write;
+
+    /// Formats into a fixed buffer, the tests run without `std`.
+    struct Text {
+        buf: [u8; 128],
+        len: usize,
+    }
+
+    impl Write for Text {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            self.buf
+                .get_mut(self.len..end)
+                .ok_or(fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.len = end;
+
+            Ok(())
+        }
+    }
+
+    fn display(transaction: &Transaction, expected: &str) {
+        let mut text = Text {
+            buf: [0; 128],
+            len: 0,
+        };
+        write!(text, "{}", transaction).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(&text.buf[..text.len]).unwrap(),
+            expected
+        );
+    }
+
+    fn transaction(index: u8) -> Transaction {
+        Transaction::write(Register::CTRL1, &[index])
+    }
+
+    #[test]
+    fn display_transactions() {
+        display(
+            &Transaction::write(Register::CTRL1, &[0x77]),
+            "write CTRL1 (0x20): 77",
+        );
+        display(
+            &Transaction::read(Register::OUT_X_L, &[0x10, 0x00, 0xf0]),
+            "read  OUT_X_L (0x28): 10 00 f0",
+        );
+        display(
+            &Transaction::new(Access::Read, Register::STATUS, &[0], false, Some(1234)),
+            "[1234] read  STATUS (0x27): 00 failed",
+        );
+    }
+
+    #[test]
+    fn display_truncated() {
+        let data = [0xab; TRACE_DATA + 2];
+        let transaction = Transaction::read(Register::OUT_X_L, &data);
+        assert!(transaction.is_truncated());
+        assert_eq!(transaction.data(), &data[..TRACE_DATA]);
+
+        display(
+            &transaction,
+            "read  OUT_X_L (0x28): ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab ... (18 bytes)",
+        );
+    }
+
+    #[test]
+    fn ring_keeps_the_newest() {
+        let mut buffer = TraceBuffer::<3>::new();
+        assert_eq!(buffer.iter().count(), 0);
+
+        for index in 0..2 {
+            buffer.record(&transaction(index));
+        }
+        assert!(buffer.iter().eq([transaction(0), transaction(1)].iter()));
+
+        for index in 2..5 {
+            buffer.record(&transaction(index));
+        }
+        assert_eq!(buffer.total(), 5);
+        assert!(buffer
+            .iter()
+            .eq([transaction(2), transaction(3), transaction(4)].iter()));
+
+        buffer.clear();
+        assert_eq!(buffer.iter().count(), 0);
+        assert_eq!(buffer.total(), 0);
+    }
+
+    #[test]
+    fn empty_ring_counts() {
+        let mut buffer = TraceBuffer::<0>::new();
+        buffer.record(&transaction(0));
+
+        assert_eq!(buffer.iter().count(), 0);
+        assert_eq!(buffer.total(), 1);
+    }
+
+    #[test]
+    fn records_accesses() {
+        fn clock() -> u32 {
+            42
+        }
+
+        let mut traced = Traced::new(FakeDevice::new(), TraceBuffer::<4>::new()).with_clock(clock);
+        traced.write_register(Register::CTRL1, 0x77).unwrap();
+        assert_eq!(traced.read_register(Register::WHOAMI).unwrap(), 0x33);
+
+        let mut buf = [0; 2];
+        traced.read_registers(Register::CTRL1, &mut buf).unwrap();
+
+        let stamped = |transaction: Transaction| Transaction {
+            timestamp: Some(42),
+            ..transaction
+        };
+        assert!(traced.sink().iter().eq([
+            stamped(Transaction::write(Register::CTRL1, &[0x77])),
+            stamped(Transaction::read(Register::WHOAMI, &[0x33])),
+            stamped(Transaction::read(Register::CTRL1, &[0x77, 0x00])),
+        ]
+        .iter()));
+    }
+}
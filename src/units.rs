@@ -0,0 +1,136 @@
+//! Conversions to and from the typed quantities of the `uom` crate.
+
+use uom::si::acceleration::{meter_per_second_squared, standard_gravity};
+use uom::si::f32::{Acceleration, ThermodynamicTemperature, Time};
+use uom::si::thermodynamic_temperature::degree_celsius;
+use uom::si::time::second;
+
+use crate::{ConfigError, DataRate, Duration, Error, Lis3dh, Lis3dhCore, Range, Threshold};
+
+impl Threshold {
+    /// Convert an acceleration to a threshold for the given range.
+    /// See [`Threshold::g`].
+    pub fn acceleration(range: Range, acceleration: Acceleration) -> Result<Self, ConfigError> {
+        Self::g(range, acceleration.get::<standard_gravity>())
+    }
+
+    /// The acceleration this threshold corresponds to at the given range.
+    pub fn to_acceleration(self, range: Range) -> Acceleration {
+        Acceleration::new::<standard_gravity>(
+            (self.0 as u32 * range.as_mg() as u32) as f32 / 1000.0,
+        )
+    }
+}
+
+impl Duration {
+    /// Convert a time to a duration at the given output data rate.
+    /// See [`Duration::seconds`].
+    pub fn time(output_data_rate: DataRate, time: Time) -> Result<Self, ConfigError> {
+        Self::seconds(output_data_rate, time.get::<second>())
+    }
+
+    /// The time this duration corresponds to at the given output data rate,
+    /// or `None` when powered down.
+    pub fn to_time(self, output_data_rate: DataRate) -> Option<Time> {
+        match output_data_rate {
+            DataRate::PowerDown => None,
+            odr => Some(Time::new::<second>(self.0 as f32 / odr.sample_rate())),
+        }
+    }
+}
+
+impl<CORE> Lis3dh<CORE>
+where
+    CORE: Lis3dhCore,
+{
+    /// Get the acceleration of the x, y and z axes.
+    pub fn acceleration(
+        &mut self,
+    ) -> Result<[Acceleration; 3], Error<CORE::BusError, CORE::PinError>> {
+        let acc = self.accel_si()?;
+
+        Ok([
+            Acceleration::new::<meter_per_second_squared>(acc.x),
+            Acceleration::new::<meter_per_second_squared>(acc.y),
+            Acceleration::new::<meter_per_second_squared>(acc.z),
+        ])
+    }
+
+    /// Get the temperature, see [`Lis3dh::get_temp_outf`].
+    pub fn temperature(
+        &mut self,
+    ) -> Result<ThermodynamicTemperature, Error<CORE::BusError, CORE::PinError>> {
+        Ok(ThermodynamicTemperature::new::<degree_celsius>(
+            self.get_temp_outf()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::FakeDevice;
+    use crate::register::Register;
+    use crate::Configuration;
+    use uom::si::time::millisecond;
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-4, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn threshold() {
+        let acceleration = Acceleration::new::<standard_gravity>(1.1);
+        let threshold = Threshold::acceleration(Range::G2, acceleration).unwrap();
+        assert_eq!(threshold.bits(), 69);
+        assert_close(
+            threshold
+                .to_acceleration(Range::G2)
+                .get::<standard_gravity>(),
+            1.104,
+        );
+
+        let acceleration = Acceleration::new::<meter_per_second_squared>(-1.0);
+        assert!(matches!(
+            Threshold::acceleration(Range::G2, acceleration),
+            Err(ConfigError::ThresholdOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn duration() {
+        let time = Time::new::<millisecond>(25.0);
+        let duration = Duration::time(DataRate::Hz_400, time).unwrap();
+        assert_eq!(duration.bits(), 10);
+        assert_close(
+            duration.to_time(DataRate::Hz_400).unwrap().get::<second>(),
+            0.025,
+        );
+        assert!(duration.to_time(DataRate::PowerDown).is_none());
+
+        let time = Time::new::<second>(1.0);
+        assert!(matches!(
+            Duration::time(DataRate::Hz_400, time),
+            Err(ConfigError::DurationOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn readings() {
+        let mut lis3dh =
+            Lis3dh::new_with_core(FakeDevice::new(), Configuration::default()).unwrap();
+
+        // 1000 counts of 1mg, left-justified, and 2°C above the reference
+        let registers = &mut lis3dh.core_mut().registers;
+        registers[Register::OUT_Z_L.addr() as usize] = 0x80;
+        registers[Register::OUT_Z_H.addr() as usize] = 0x3e;
+        registers[Register::OUT_ADC3_H.addr() as usize] = 2;
+
+        let [x, y, z] = lis3dh.acceleration().unwrap();
+        assert_close(x.get::<standard_gravity>(), 0.0);
+        assert_close(y.get::<standard_gravity>(), 0.0);
+        assert_close(z.get::<standard_gravity>(), 1.0);
+
+        assert_close(lis3dh.temperature().unwrap().get::<degree_celsius>(), 27.0);
+    }
+}